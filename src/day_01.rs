@@ -1,5 +1,12 @@
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
 use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 struct Data {
     pub col_1: Vec<u64>,
@@ -42,6 +49,212 @@ fn get_data() -> Data {
     Data { col_1, col_2 }
 }
 
+// Sorted run of values spilled to a temporary file, removed once dropped.
+struct Run {
+    path: PathBuf,
+}
+
+impl Run {
+    fn write<I: Iterator<Item = io::Result<u64>>>(values: I) -> io::Result<Run> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let run = Run {
+            path: std::env::temp_dir().join(format!(
+                "aoc2024_day01_{}_{}.run",
+                process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            )),
+        };
+        let mut writer = BufWriter::new(File::create(&run.path)?);
+        for value in values {
+            writer.write_all(&value?.to_le_bytes())?;
+        }
+        writer.flush()?;
+        Ok(run)
+    }
+
+    fn spill(values: &mut Vec<u64>) -> io::Result<Run> {
+        values.sort_unstable();
+        let run = Run::write(values.iter().map(|v| Ok(*v)))?;
+        values.clear();
+        Ok(run)
+    }
+
+    fn reader(&self) -> io::Result<RunReader> {
+        Ok(RunReader {
+            reader: BufReader::new(File::open(&self.path)?),
+        })
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+struct RunReader {
+    reader: BufReader<File>,
+}
+
+impl Iterator for RunReader {
+    type Item = io::Result<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut bytes = [0u8; 8];
+        match self.reader.read_exact(&mut bytes) {
+            Ok(()) => Some(Ok(u64::from_le_bytes(bytes))),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+// K-way merge of sorted runs, yielding every value in ascending order.
+struct MergedRuns {
+    readers: Vec<RunReader>,
+    heap: BinaryHeap<Reverse<(u64, usize)>>,
+}
+
+impl MergedRuns {
+    fn new(runs: &[Run]) -> io::Result<MergedRuns> {
+        let mut readers = Vec::with_capacity(runs.len());
+        let mut heap = BinaryHeap::with_capacity(runs.len());
+        for (idx, run) in runs.iter().enumerate() {
+            let mut reader = run.reader()?;
+            if let Some(value) = reader.next() {
+                heap.push(Reverse((value?, idx)));
+            }
+            readers.push(reader);
+        }
+        Ok(MergedRuns { readers, heap })
+    }
+}
+
+impl Iterator for MergedRuns {
+    type Item = io::Result<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((value, idx)) = self.heap.pop()?;
+        match self.readers[idx].next() {
+            Some(Ok(next)) => self.heap.push(Reverse((next, idx))),
+            Some(Err(e)) => return Some(Err(e)),
+            None => (),
+        }
+        Some(Ok(value))
+    }
+}
+
+// Most runs merged at once, which bounds the open files and read buffers of a
+// merge no matter how many runs were spilled.
+const MAX_FAN_IN: usize = 64;
+
+// Merges groups of runs into bigger runs until at most `fan_in` are left.
+fn reduce_runs(mut runs: Vec<Run>, fan_in: usize) -> io::Result<Vec<Run>> {
+    let fan_in = fan_in.max(2);
+    while runs.len() > fan_in {
+        runs = runs
+            .chunks(fan_in)
+            .map(|group| Run::write(MergedRuns::new(group)?))
+            .collect::<io::Result<Vec<Run>>>()?;
+    }
+    Ok(runs)
+}
+
+struct SpilledData {
+    col_1: Vec<Run>,
+    col_2: Vec<Run>,
+}
+
+fn spill_data<R: BufRead>(input: R, run_size: usize) -> io::Result<SpilledData> {
+    let run_size = run_size.max(1);
+    let mut data = SpilledData {
+        col_1: Vec::new(),
+        col_2: Vec::new(),
+    };
+    let mut buf_1 = Vec::with_capacity(run_size);
+    let mut buf_2 = Vec::with_capacity(run_size);
+    for line in input.lines() {
+        let line = line?;
+        let mut values = line.split_whitespace().map(|v| {
+            v.parse::<u64>()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        });
+        match (values.next(), values.next()) {
+            (Some(val_1), Some(val_2)) => {
+                buf_1.push(val_1?);
+                buf_2.push(val_2?);
+            }
+            (None, None) => continue,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("expected two location ids, got {:?}", line),
+                ))
+            }
+        }
+        if buf_1.len() == run_size {
+            data.col_1.push(Run::spill(&mut buf_1)?);
+            data.col_2.push(Run::spill(&mut buf_2)?);
+        }
+    }
+    if !buf_1.is_empty() {
+        data.col_1.push(Run::spill(&mut buf_1)?);
+        data.col_2.push(Run::spill(&mut buf_2)?);
+    }
+    Ok(data)
+}
+
+fn streaming_challenge_01(data: &SpilledData) -> io::Result<u64> {
+    let mut sum = 0;
+    for (val_1, val_2) in MergedRuns::new(&data.col_1)?.zip(MergedRuns::new(&data.col_2)?) {
+        sum += val_1?.abs_diff(val_2?);
+    }
+    Ok(sum)
+}
+
+// Merge-join of both sorted columns: each distinct value contributes
+// value * (occurrences on the left) * (occurrences on the right).
+fn streaming_challenge_02(data: &SpilledData) -> io::Result<u64> {
+    let mut sum = 0;
+    let mut left = MergedRuns::new(&data.col_1)?.peekable();
+    let mut right = MergedRuns::new(&data.col_2)?.peekable();
+    while let Some(value) = left.next() {
+        let value = value?;
+        let mut count_left = 1;
+        while let Some(Ok(next)) = left.peek() {
+            if *next != value {
+                break;
+            }
+            left.next();
+            count_left += 1;
+        }
+        let mut count_right = 0;
+        while let Some(next) = right.peek() {
+            match next {
+                Ok(next) if *next < value => (),
+                Ok(next) if *next == value => count_right += 1,
+                Ok(_) => break,
+                Err(_) => return Err(right.next().unwrap().unwrap_err()),
+            }
+            right.next();
+        }
+        sum += value * count_left * count_right;
+    }
+    Ok(sum)
+}
+
+pub fn streaming_parts(path: &str, run_size: usize) -> io::Result<(u64, u64)> {
+    let data = spill_data(BufReader::new(File::open(path)?), run_size)?;
+    let data = SpilledData {
+        col_1: reduce_runs(data.col_1, MAX_FAN_IN)?,
+        col_2: reduce_runs(data.col_2, MAX_FAN_IN)?,
+    };
+    Ok((
+        streaming_challenge_01(&data)?,
+        streaming_challenge_02(&data)?,
+    ))
+}
+
 pub fn part_1() -> u64 {
     let data = get_data();
     challenge_01(data)
@@ -79,4 +292,44 @@ mod test {
         // Assert
         assert_eq!(actual, 31);
     }
+
+    #[test]
+    fn check_streaming_example() {
+        // Arrange
+        let input = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
+        // Act
+        let data = spill_data(io::Cursor::new(input), 4).unwrap();
+        // Assert
+        assert_eq!(data.col_1.len(), 2);
+        assert_eq!(streaming_challenge_01(&data).unwrap(), 11);
+        assert_eq!(streaming_challenge_02(&data).unwrap(), 31);
+    }
+
+    #[test]
+    fn check_streaming_matches_in_memory() {
+        // Arrange
+        let input = fs::read_to_string("data/day01_01.txt").unwrap();
+        // Act
+        let data = spill_data(io::Cursor::new(input), 97).unwrap();
+        // Assert
+        assert_eq!(streaming_challenge_01(&data).unwrap(), part_1());
+        assert_eq!(streaming_challenge_02(&data).unwrap(), part_2());
+    }
+
+    #[test]
+    fn check_reduced_runs() {
+        // Arrange
+        let input = fs::read_to_string("data/day01_01.txt").unwrap();
+        let data = spill_data(io::Cursor::new(input), 7).unwrap();
+        // Act
+        let data = SpilledData {
+            col_1: reduce_runs(data.col_1, 3).unwrap(),
+            col_2: reduce_runs(data.col_2, 3).unwrap(),
+        };
+        // Assert
+        assert!(data.col_1.len() <= 3);
+        assert!(data.col_2.len() <= 3);
+        assert_eq!(streaming_challenge_01(&data).unwrap(), part_1());
+        assert_eq!(streaming_challenge_02(&data).unwrap(), part_2());
+    }
 }
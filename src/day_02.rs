@@ -1,33 +1,277 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
 use std::fs;
+use std::iter;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Increasing,
+    Decreasing,
+    Either,
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "increasing" => Ok(Direction::Increasing),
+            "decreasing" => Ok(Direction::Decreasing),
+            "either" => Ok(Direction::Either),
+            _ => Err(format!("unknown direction: {}", s)),
+        }
+    }
+}
+
+impl Direction {
+    fn signs(&self) -> &'static [i64] {
+        match self {
+            Direction::Increasing => &[1],
+            Direction::Decreasing => &[-1],
+            Direction::Either => &[1, -1],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SafetyPolicy {
+    pub min_step: i64,
+    pub max_step: i64,
+    pub direction: Direction,
+    pub allow_equal: bool,
+}
+
+impl SafetyPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_step < 0 || self.min_step > self.max_step {
+            return Err(format!(
+                "invalid step range: need 0 <= min-step <= max-step, got {}..={}",
+                self.min_step, self.max_step
+            ));
+        }
+        Ok(())
+    }
+
+    fn step_ok(&self, from: i64, to: i64, sign: i64) -> bool {
+        let diff = to - from;
+        if diff == 0 {
+            return self.allow_equal;
+        }
+        diff.signum() == sign && diff.abs() >= self.min_step && diff.abs() <= self.max_step
+    }
+}
+
+impl Default for SafetyPolicy {
+    fn default() -> Self {
+        SafetyPolicy {
+            min_step: 1,
+            max_step: 3,
+            direction: Direction::Either,
+            allow_equal: false,
+        }
+    }
+}
 
 struct Report {
     levels: Vec<i64>,
 }
 
+#[derive(Debug, PartialEq)]
+struct Removal {
+    count: usize,
+    indices: Vec<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Violation {
+    ZeroStep,
+    StepTooSmall,
+    StepTooLarge,
+    DirectionChange,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            Violation::ZeroStep => "zero step",
+            Violation::StepTooSmall => "step too small",
+            Violation::StepTooLarge => "step too large",
+            Violation::DirectionChange => "direction change",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Verdict {
+    Safe,
+    Unsafe {
+        first: usize,
+        second: usize,
+        reason: Violation,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DampenedVerdict {
+    Safe,
+    Rescued {
+        first: usize,
+        second: usize,
+        reason: Violation,
+        removed: usize,
+    },
+    Unsafe {
+        first: usize,
+        second: usize,
+        reason: Violation,
+    },
+}
+
 impl Report {
-    fn safe(&self) -> bool {
-        let mut orientation = 0i64;
+    fn safe(&self, policy: &SafetyPolicy) -> bool {
+        self.verdict(policy) == Verdict::Safe
+    }
+
+    fn verdict(&self, policy: &SafetyPolicy) -> Verdict {
+        let mut orientation = match policy.direction {
+            Direction::Increasing => 1i64,
+            Direction::Decreasing => -1i64,
+            Direction::Either => 0i64,
+        };
         for i in 1..self.levels.len() {
-            let diff = self.levels[i - 1] - self.levels[i];
-            if diff < -3 || diff > 3 {
-                return false;
+            let diff = self.levels[i] - self.levels[i - 1];
+            let reason = if diff == 0 {
+                if policy.allow_equal {
+                    continue;
+                }
+                Violation::ZeroStep
+            } else if diff.abs() < policy.min_step {
+                Violation::StepTooSmall
+            } else if diff.abs() > policy.max_step {
+                Violation::StepTooLarge
+            } else if orientation != 0 && orientation != diff.signum() {
+                Violation::DirectionChange
+            } else {
+                orientation = diff.signum();
+                continue;
+            };
+            return Verdict::Unsafe {
+                first: i - 1,
+                second: i,
+                reason,
+            };
+        }
+        Verdict::Safe
+    }
+
+    // Index of the first level whose step to the next kept level breaks the
+    // policy for a fixed direction, ignoring the level at `skip`.
+    fn first_violation(
+        &self,
+        policy: &SafetyPolicy,
+        sign: i64,
+        skip: Option<usize>,
+    ) -> Option<usize> {
+        let mut previous: Option<usize> = None;
+        for i in 0..self.levels.len() {
+            if Some(i) == skip {
+                continue;
             }
-            if orientation == 0 && diff != 0 {
-                orientation = diff;
-            } else if (diff == 0) || (orientation < 0 && diff > 0) || (orientation > 0 && diff < 0)
-            {
-                return false;
+            if let Some(p) = previous {
+                if !policy.step_ok(self.levels[p], self.levels[i], sign) {
+                    return Some(p);
+                }
             }
+            previous = Some(i);
         }
-        true
+        None
+    }
+
+    fn safe_with_dampener(&self, policy: &SafetyPolicy) -> bool {
+        !matches!(
+            self.dampened_verdict(policy),
+            DampenedVerdict::Unsafe { .. }
+        )
     }
 
-    fn safe_with_naive_dampener(&self) -> bool {
-        if self.safe() {
+    // Any removal that makes the report safe has to break up the first
+    // offending pair, so only its two levels are worth trying.
+    fn dampened_verdict(&self, policy: &SafetyPolicy) -> DampenedVerdict {
+        let (first, second, reason) = match self.verdict(policy) {
+            Verdict::Safe => return DampenedVerdict::Safe,
+            Verdict::Unsafe {
+                first,
+                second,
+                reason,
+            } => (first, second, reason),
+        };
+        for &sign in policy.direction.signs() {
+            if let Some(i) = self.first_violation(policy, sign, None) {
+                for removed in [i, i + 1] {
+                    if self.first_violation(policy, sign, Some(removed)).is_none() {
+                        return DampenedVerdict::Rescued {
+                            first,
+                            second,
+                            reason,
+                            removed,
+                        };
+                    }
+                }
+            }
+        }
+        DampenedVerdict::Unsafe {
+            first,
+            second,
+            reason,
+        }
+    }
+
+    // Keeps the longest subsequence whose consecutive levels satisfy the policy
+    // and removes everything else.
+    fn min_removals(&self, policy: &SafetyPolicy) -> Removal {
+        let n = self.levels.len();
+        let mut best: Vec<usize> = Vec::new();
+        for &sign in policy.direction.signs() {
+            let mut length = vec![1usize; n];
+            let mut parent: Vec<Option<usize>> = vec![None; n];
+            for i in 0..n {
+                for j in 0..i {
+                    if length[j] + 1 > length[i]
+                        && policy.step_ok(self.levels[j], self.levels[i], sign)
+                    {
+                        length[i] = length[j] + 1;
+                        parent[i] = Some(j);
+                    }
+                }
+            }
+            if let Some(end) = (0..n).max_by_key(|&i| length[i]) {
+                if length[end] > best.len() {
+                    let mut kept = vec![end];
+                    while let Some(p) = parent[*kept.last().unwrap()] {
+                        kept.push(p);
+                    }
+                    kept.reverse();
+                    best = kept;
+                }
+            }
+        }
+        let indices: Vec<usize> = (0..n).filter(|i| !best.contains(i)).collect();
+        Removal {
+            count: indices.len(),
+            indices,
+        }
+    }
+
+    #[cfg(test)]
+    fn safe_with_naive_dampener(&self, policy: &SafetyPolicy) -> bool {
+        if self.safe(policy) {
             return true;
         } else {
             for i in 0..self.levels.len() {
-                if damper(self, i).safe() {
+                if damper(self, i).safe(policy) {
                     return true;
                 }
             }
@@ -36,17 +280,18 @@ impl Report {
     }
 }
 
+#[cfg(test)]
 fn damper(report: &Report, level: usize) -> Report {
     let mut levels = report.levels.clone();
     levels.remove(level);
     Report { levels }
 }
 
-fn challenge_01(reports: Vec<Report>) -> u64 {
+fn challenge_01(reports: Vec<Report>, policy: &SafetyPolicy) -> u64 {
     let mut safe = 0u64;
 
     reports.iter().for_each(|r| {
-        if r.safe() {
+        if r.safe(policy) {
             safe += 1;
         }
     });
@@ -54,11 +299,11 @@ fn challenge_01(reports: Vec<Report>) -> u64 {
     safe
 }
 
-fn challenge_02(reports: Vec<Report>) -> u64 {
+fn challenge_02(reports: Vec<Report>, policy: &SafetyPolicy) -> u64 {
     let mut safe = 0u64;
 
     reports.iter().for_each(|r| {
-        if r.safe_with_naive_dampener() {
+        if r.safe_with_dampener(policy) {
             safe += 1;
         }
     });
@@ -66,6 +311,171 @@ fn challenge_02(reports: Vec<Report>) -> u64 {
     safe
 }
 
+fn challenge_k_removals(reports: Vec<Report>, policy: &SafetyPolicy, max_removals: usize) -> u64 {
+    reports
+        .iter()
+        .filter(|r| r.min_removals(policy).count <= max_removals)
+        .count() as u64
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Statistics {
+    reports: u64,
+    differences: BTreeMap<i64, u64>,
+    lengths: BTreeMap<usize, u64>,
+    // Trend of each report: strictly increasing or decreasing, monotone with at
+    // least one repeated level, flat (fewer than two levels or no change at all),
+    // or mixed when it goes both up and down.
+    increasing: u64,
+    decreasing: u64,
+    non_decreasing: u64,
+    non_increasing: u64,
+    flat: u64,
+    mixed: u64,
+    safe: u64,
+    dampened: u64,
+    unrecoverable: u64,
+}
+
+impl Statistics {
+    fn to_table(&self) -> String {
+        let mut lines = vec![
+            format!("reports        {}", self.reports),
+            format!("increasing     {}", self.increasing),
+            format!("decreasing     {}", self.decreasing),
+            format!("non-decreasing {}", self.non_decreasing),
+            format!("non-increasing {}", self.non_increasing),
+            format!("flat           {}", self.flat),
+            format!("mixed          {}", self.mixed),
+            format!("safe           {}", self.safe),
+            format!("dampened       {}", self.dampened),
+            format!("unrecoverable  {}", self.unrecoverable),
+            "length         count".to_string(),
+        ];
+        for (length, count) in self.lengths.iter() {
+            lines.push(format!("{:<14} {}", length, count));
+        }
+        lines.push("difference     count".to_string());
+        for (diff, count) in self.differences.iter() {
+            lines.push(format!("{:<14} {}", diff, count));
+        }
+        lines.join("\n")
+    }
+
+    fn to_json(&self) -> String {
+        fn histogram<K: Display>(map: &BTreeMap<K, u64>) -> String {
+            map.iter()
+                .map(|(k, v)| format!("\"{}\":{}", k, v))
+                .collect::<Vec<String>>()
+                .join(",")
+        }
+        format!(
+            "{{\"reports\":{},\"increasing\":{},\"decreasing\":{},\
+             \"non_decreasing\":{},\"non_increasing\":{},\"flat\":{},\"mixed\":{},\
+             \"safe\":{},\"dampened\":{},\"unrecoverable\":{},\
+             \"lengths\":{{{}}},\"differences\":{{{}}}}}",
+            self.reports,
+            self.increasing,
+            self.decreasing,
+            self.non_decreasing,
+            self.non_increasing,
+            self.flat,
+            self.mixed,
+            self.safe,
+            self.dampened,
+            self.unrecoverable,
+            histogram(&self.lengths),
+            histogram(&self.differences)
+        )
+    }
+}
+
+fn statistics(reports: &[Report], policy: &SafetyPolicy) -> Statistics {
+    let mut stats = Statistics::default();
+    for r in reports {
+        stats.reports += 1;
+        *stats.lengths.entry(r.levels.len()).or_default() += 1;
+        let diffs: Vec<i64> = r.levels.windows(2).map(|w| w[1] - w[0]).collect();
+        for diff in diffs.iter() {
+            *stats.differences.entry(*diff).or_default() += 1;
+        }
+        let rises = diffs.iter().any(|d| *d > 0);
+        let falls = diffs.iter().any(|d| *d < 0);
+        let plateau = diffs.contains(&0);
+        match (rises, falls, plateau) {
+            (false, false, _) => stats.flat += 1,
+            (true, true, _) => stats.mixed += 1,
+            (true, false, false) => stats.increasing += 1,
+            (false, true, false) => stats.decreasing += 1,
+            (true, false, true) => stats.non_decreasing += 1,
+            (false, true, true) => stats.non_increasing += 1,
+        }
+        match r.dampened_verdict(policy) {
+            DampenedVerdict::Safe => stats.safe += 1,
+            DampenedVerdict::Rescued { .. } => stats.dampened += 1,
+            DampenedVerdict::Unsafe { .. } => stats.unrecoverable += 1,
+        }
+    }
+    stats
+}
+
+fn explain(reports: &[Report], policy: &SafetyPolicy) -> String {
+    let rows: Vec<[String; 4]> = reports
+        .iter()
+        .enumerate()
+        .map(|(idx, r)| {
+            let levels = format!("{:?}", r.levels);
+            let (verdict, detail) = match r.dampened_verdict(policy) {
+                DampenedVerdict::Safe => ("safe".to_string(), String::new()),
+                DampenedVerdict::Rescued {
+                    first,
+                    second,
+                    reason,
+                    removed,
+                } => (
+                    "dampened".to_string(),
+                    format!(
+                        "{} at ({}, {}), rescued by removing level {}",
+                        reason, first, second, removed
+                    ),
+                ),
+                DampenedVerdict::Unsafe {
+                    first,
+                    second,
+                    reason,
+                } => (
+                    "unsafe".to_string(),
+                    format!("{} at ({}, {})", reason, first, second),
+                ),
+            };
+            [(idx + 1).to_string(), levels, verdict, detail]
+        })
+        .collect();
+    let header = ["#", "levels", "verdict", "detail"].map(String::from);
+    let widths: Vec<usize> = (0..4)
+        .map(|c| {
+            rows.iter()
+                .chain(iter::once(&header))
+                .map(|row| row[c].len())
+                .max()
+                .unwrap()
+        })
+        .collect();
+    iter::once(&header)
+        .chain(rows.iter())
+        .map(|row| {
+            row.iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<String>>()
+                .join(" | ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
 fn get_data() -> Vec<Report> {
     let mut reports: Vec<Report> = Vec::with_capacity(1000);
     for line in fs::read_to_string("data/day02_01.txt").unwrap().lines() {
@@ -80,19 +490,53 @@ fn get_data() -> Vec<Report> {
 }
 
 pub fn part_1() -> u64 {
-    let reports = get_data();
-    challenge_01(reports)
+    part_1_with_policy(&SafetyPolicy::default())
 }
 
 pub fn part_2() -> u64 {
+    part_2_with_policy(&SafetyPolicy::default())
+}
+
+pub fn part_1_with_policy(policy: &SafetyPolicy) -> u64 {
+    let reports = get_data();
+    challenge_01(reports, policy)
+}
+
+pub fn part_2_with_policy(policy: &SafetyPolicy) -> u64 {
     let reports = get_data();
-    challenge_02(reports)
+    challenge_02(reports, policy)
+}
+
+pub fn explain_with_policy(policy: &SafetyPolicy) -> String {
+    let reports = get_data();
+    explain(&reports, policy)
+}
+
+pub fn statistics_with_policy(policy: &SafetyPolicy, json: bool) -> String {
+    let reports = get_data();
+    let stats = statistics(&reports, policy);
+    if json {
+        stats.to_json()
+    } else {
+        stats.to_table()
+    }
+}
+
+pub fn safe_within_removals(policy: &SafetyPolicy, max_removals: usize) -> u64 {
+    let reports = get_data();
+    challenge_k_removals(reports, policy, max_removals)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    fn report(levels: &[i64]) -> Report {
+        Report {
+            levels: levels.to_vec(),
+        }
+    }
+
     #[test]
     fn check_challenge_01() {
         // Arrange
@@ -117,7 +561,7 @@ mod test {
             },
         ];
         // Act
-        let actual = challenge_01(reports);
+        let actual = challenge_01(reports, &SafetyPolicy::default());
         // Assert
         assert_eq!(actual, 2);
     }
@@ -146,8 +590,259 @@ mod test {
             },
         ];
         // Act
-        let actual = challenge_02(reports);
+        let actual = challenge_02(reports, &SafetyPolicy::default());
         // Assert
         assert_eq!(actual, 4);
     }
+
+    #[test]
+    fn check_custom_policy() {
+        // Arrange
+        let increasing = SafetyPolicy {
+            direction: Direction::Increasing,
+            ..SafetyPolicy::default()
+        };
+        let plateaus = SafetyPolicy {
+            allow_equal: true,
+            ..SafetyPolicy::default()
+        };
+        let wide_steps = SafetyPolicy {
+            min_step: 2,
+            max_step: 5,
+            ..SafetyPolicy::default()
+        };
+        // Act and Assert
+        assert!(report(&[1, 3, 6, 7, 9]).safe(&increasing));
+        assert!(!report(&[7, 6, 4, 2, 1]).safe(&increasing));
+        assert!(report(&[8, 6, 4, 4, 1]).safe(&plateaus));
+        assert!(!report(&[8, 6, 4, 4, 1]).safe(&SafetyPolicy::default()));
+        assert!(report(&[1, 3, 8, 10]).safe(&wide_steps));
+        assert!(!report(&[1, 3, 6, 7, 9]).safe(&wide_steps));
+    }
+
+    #[test]
+    fn check_dampener_matches_naive() {
+        // Arrange
+        let policies = [
+            SafetyPolicy::default(),
+            SafetyPolicy {
+                direction: Direction::Decreasing,
+                ..SafetyPolicy::default()
+            },
+            SafetyPolicy {
+                min_step: 0,
+                max_step: 2,
+                allow_equal: true,
+                ..SafetyPolicy::default()
+            },
+        ];
+        for len in 0..=6u32 {
+            for code in 0..5i64.pow(len) {
+                let levels: Vec<i64> = (0..len).map(|i| code / 5i64.pow(i) % 5).collect();
+                let report = Report { levels };
+                for policy in policies.iter() {
+                    // Act
+                    let actual = report.safe_with_dampener(policy);
+                    // Assert
+                    assert_eq!(
+                        actual,
+                        report.safe_with_naive_dampener(policy),
+                        "{:?} with {:?}",
+                        report.levels,
+                        policy
+                    );
+                    assert_eq!(actual, report.min_removals(policy).count <= 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn check_min_removals() {
+        // Arrange
+        let policy = SafetyPolicy::default();
+        // Act and Assert
+        assert_eq!(
+            report(&[7, 6, 4, 2, 1]).min_removals(&policy),
+            Removal {
+                count: 0,
+                indices: vec![]
+            }
+        );
+        assert_eq!(
+            report(&[1, 3, 2, 4, 5]).min_removals(&policy),
+            Removal {
+                count: 1,
+                indices: vec![2]
+            }
+        );
+        assert_eq!(
+            report(&[1, 2, 7, 8, 9]).min_removals(&policy),
+            Removal {
+                count: 2,
+                indices: vec![0, 1]
+            }
+        );
+        assert_eq!(
+            report(&[10, 1, 2, 20, 3, 4, 30]).min_removals(&policy),
+            Removal {
+                count: 3,
+                indices: vec![0, 3, 6]
+            }
+        );
+    }
+
+    #[test]
+    fn check_challenge_k_removals() {
+        // Arrange
+        let reports = || {
+            vec![
+                report(&[7, 6, 4, 2, 1]),
+                report(&[1, 2, 7, 8, 9]),
+                report(&[9, 7, 6, 2, 1]),
+                report(&[1, 3, 2, 4, 5]),
+                report(&[8, 6, 4, 4, 1]),
+                report(&[1, 3, 6, 7, 9]),
+            ]
+        };
+        let policy = SafetyPolicy::default();
+        // Act and Assert
+        assert_eq!(challenge_k_removals(reports(), &policy, 0), 2);
+        assert_eq!(challenge_k_removals(reports(), &policy, 1), 4);
+        assert_eq!(challenge_k_removals(reports(), &policy, 2), 6);
+    }
+
+    #[test]
+    fn check_verdicts() {
+        // Arrange
+        let policy = SafetyPolicy::default();
+        // Act and Assert
+        assert_eq!(report(&[7, 6, 4, 2, 1]).verdict(&policy), Verdict::Safe);
+        assert_eq!(
+            report(&[1, 2, 7, 8, 9]).verdict(&policy),
+            Verdict::Unsafe {
+                first: 1,
+                second: 2,
+                reason: Violation::StepTooLarge
+            }
+        );
+        assert_eq!(
+            report(&[1, 3, 2, 4, 5]).verdict(&policy),
+            Verdict::Unsafe {
+                first: 1,
+                second: 2,
+                reason: Violation::DirectionChange
+            }
+        );
+        assert_eq!(
+            report(&[8, 6, 4, 4, 1]).dampened_verdict(&policy),
+            DampenedVerdict::Rescued {
+                first: 2,
+                second: 3,
+                reason: Violation::ZeroStep,
+                removed: 2
+            }
+        );
+        assert_eq!(
+            report(&[9, 7, 6, 2, 1]).dampened_verdict(&policy),
+            DampenedVerdict::Unsafe {
+                first: 2,
+                second: 3,
+                reason: Violation::StepTooLarge
+            }
+        );
+    }
+
+    #[test]
+    fn check_explain() {
+        // Arrange
+        let reports = vec![report(&[7, 6, 4, 2, 1]), report(&[1, 3, 2, 4, 5])];
+        let expected = "\
+# | levels          | verdict  | detail
+1 | [7, 6, 4, 2, 1] | safe     |
+2 | [1, 3, 2, 4, 5] | dampened | direction change at (1, 2), rescued by removing level 1";
+        // Act
+        let actual = explain(&reports, &SafetyPolicy::default());
+        // Assert
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn check_statistics() {
+        // Arrange
+        let reports = vec![
+            report(&[7, 6, 4, 2, 1]),
+            report(&[1, 2, 7, 8, 9]),
+            report(&[9, 7, 6, 2, 1]),
+            report(&[1, 3, 2, 4, 5]),
+            report(&[8, 6, 4, 4, 1]),
+            report(&[1, 3, 6, 7, 9]),
+        ];
+        // Act
+        let actual = statistics(&reports, &SafetyPolicy::default());
+        // Assert
+        assert_eq!(actual.reports, 6);
+        assert_eq!(
+            (actual.increasing, actual.decreasing, actual.mixed),
+            (2, 2, 1)
+        );
+        assert_eq!((actual.non_decreasing, actual.non_increasing), (0, 1));
+        assert_eq!(actual.flat, 0);
+        assert_eq!(
+            (actual.safe, actual.dampened, actual.unrecoverable),
+            (2, 2, 2)
+        );
+        assert_eq!(actual.lengths, BTreeMap::from([(5, 6)]));
+        assert_eq!(actual.differences.values().sum::<u64>(), 24);
+        assert_eq!(actual.differences[&-2], 5);
+        assert_eq!(actual.differences[&0], 1);
+    }
+
+    #[test]
+    fn check_statistics_json() {
+        // Arrange
+        let reports = vec![report(&[1, 3, 2]), report(&[5, 4])];
+        let expected = "{\"reports\":2,\"increasing\":0,\"decreasing\":1,\
+                        \"non_decreasing\":0,\"non_increasing\":0,\"flat\":0,\"mixed\":1,\
+                        \"safe\":1,\"dampened\":1,\"unrecoverable\":0,\
+                        \"lengths\":{\"2\":1,\"3\":1},\"differences\":{\"-1\":2,\"2\":1}}";
+        // Act
+        let actual = statistics(&reports, &SafetyPolicy::default()).to_json();
+        // Assert
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn check_policy_validation() {
+        // Arrange
+        let mut policy = SafetyPolicy::default();
+        // Act and Assert
+        assert!(policy.validate().is_ok());
+        policy.min_step = 0;
+        assert!(policy.validate().is_ok());
+        policy.min_step = 5;
+        policy.max_step = 2;
+        assert!(policy.validate().is_err());
+        policy.min_step = -1;
+        assert!(policy.validate().is_err());
+    }
+
+    #[test]
+    fn check_statistics_trends() {
+        // Arrange
+        let reports = vec![
+            report(&[]),
+            report(&[4]),
+            report(&[3, 3, 3]),
+            report(&[1, 2, 2, 5]),
+            report(&[8, 6, 4, 4, 1]),
+        ];
+        // Act
+        let actual = statistics(&reports, &SafetyPolicy::default());
+        // Assert
+        assert_eq!(actual.flat, 3);
+        assert_eq!(actual.non_decreasing, 1);
+        assert_eq!(actual.non_increasing, 1);
+        assert_eq!(actual.increasing + actual.decreasing + actual.mixed, 0);
+    }
 }
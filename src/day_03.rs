@@ -1,76 +1,1070 @@
 use regex::Regex;
+use std::cell::OnceCell;
+use std::fmt;
+use std::fmt::{Display, Formatter};
 use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::mem;
+use std::ops::Range;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
-fn challenge_01(mem: &str) -> u64 {
-    let mut sum = 0u64;
-    let re = Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)").unwrap();
-    for (_, [x, y]) in re.captures_iter(mem).map(|group| group.extract()) {
-        sum += x.parse::<u64>().unwrap() * y.parse::<u64>().unwrap();
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct State {
+    accumulator: i64,
+    enabled: bool,
+    // Set once an instruction could not be added without leaving the i64 range;
+    // the accumulator keeps its last valid value.
+    overflowed: bool,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            accumulator: 0,
+            enabled: true,
+            overflowed: false,
+        }
+    }
+}
+
+impl State {
+    fn add(&mut self, value: Option<i64>) {
+        match value.and_then(|v| self.accumulator.checked_add(v)) {
+            Some(sum) => self.accumulator = sum,
+            None => self.overflowed = true,
+        }
+    }
+
+    fn result(&self) -> Result<i64, String> {
+        if self.overflowed {
+            return Err("accumulator overflowed".to_string());
+        }
+        Ok(self.accumulator)
+    }
+}
+
+trait Operation {
+    fn name(&self) -> &str;
+    fn arity(&self) -> usize;
+    // Instructions whose result does not fit an i64 are not matches.
+    fn validate(&self, operands: &[i64]) -> bool {
+        self.arity() == 0 || self.value(operands).is_some()
+    }
+    // Value of the instruction when used as an operand in nested mode.
+    fn value(&self, _operands: &[i64]) -> Option<i64> {
+        None
+    }
+    // Adds the instruction's value while enabled; operations without a value
+    // override this.
+    fn execute(&self, operands: &[i64], state: &mut State) {
+        if state.enabled {
+            state.add(self.value(operands));
+        }
+    }
+}
+
+struct Mul;
+struct Do;
+struct Dont;
+struct Add;
+struct Sub;
+struct Div;
+struct Reset;
+
+impl Operation for Mul {
+    fn name(&self) -> &str {
+        "mul"
+    }
+    fn arity(&self) -> usize {
+        2
+    }
+    fn value(&self, operands: &[i64]) -> Option<i64> {
+        operands[0].checked_mul(operands[1])
+    }
+}
+
+impl Operation for Do {
+    fn name(&self) -> &str {
+        "do"
+    }
+    fn arity(&self) -> usize {
+        0
+    }
+    fn execute(&self, _operands: &[i64], state: &mut State) {
+        state.enabled = true;
+    }
+}
+
+impl Operation for Dont {
+    fn name(&self) -> &str {
+        "don't"
+    }
+    fn arity(&self) -> usize {
+        0
+    }
+    fn execute(&self, _operands: &[i64], state: &mut State) {
+        state.enabled = false;
+    }
+}
+
+impl Operation for Add {
+    fn name(&self) -> &str {
+        "add"
+    }
+    fn arity(&self) -> usize {
+        2
+    }
+    fn value(&self, operands: &[i64]) -> Option<i64> {
+        operands[0].checked_add(operands[1])
+    }
+}
+
+impl Operation for Sub {
+    fn name(&self) -> &str {
+        "sub"
+    }
+    fn arity(&self) -> usize {
+        2
+    }
+    fn value(&self, operands: &[i64]) -> Option<i64> {
+        operands[0].checked_sub(operands[1])
+    }
+}
+
+impl Operation for Div {
+    fn name(&self) -> &str {
+        "div"
+    }
+    fn arity(&self) -> usize {
+        2
+    }
+    // None for division by zero as well as the overflowing i64::MIN / -1.
+    fn value(&self, operands: &[i64]) -> Option<i64> {
+        operands[0].checked_div(operands[1])
+    }
+}
+
+impl Operation for Reset {
+    fn name(&self) -> &str {
+        "reset"
+    }
+    fn arity(&self) -> usize {
+        0
+    }
+    fn execute(&self, _operands: &[i64], state: &mut State) {
+        *state = State::default();
+    }
+}
+
+fn operation(name: &str) -> Option<Box<dyn Operation>> {
+    match name {
+        "mul" => Some(Box::new(Mul)),
+        "do" => Some(Box::new(Do)),
+        "don't" => Some(Box::new(Dont)),
+        "add" => Some(Box::new(Add)),
+        "sub" => Some(Box::new(Sub)),
+        "div" => Some(Box::new(Div)),
+        "reset" => Some(Box::new(Reset)),
+        _ => None,
     }
-    sum
 }
 
-fn next_instruction(mem: &str) -> (Option<usize>, usize) {
-    let next_do = mem.find("do()");
-    let next_dont = mem.find("don't()");
-    match (next_do, next_dont) {
-        (Some(i), Some(j)) => {
-            if i < j {
-                (Some(i), "do()".len())
-            } else {
-                (Some(j), "don't()".len())
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grammar {
+    pub min_digits: usize,
+    pub max_digits: usize,
+    pub signed: bool,
+    pub whitespace: bool,
+    pub case_sensitive: bool,
+}
+
+impl Default for Grammar {
+    fn default() -> Self {
+        Grammar {
+            min_digits: 1,
+            max_digits: 3,
+            signed: false,
+            whitespace: false,
+            case_sensitive: true,
+        }
+    }
+}
+
+// One `key = value` per line, `#` starts a comment, missing keys keep the
+// puzzle's defaults.
+impl FromStr for Grammar {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut grammar = Grammar::default();
+        for line in s.lines() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(format!("expected key = value, got {:?}", line))?;
+            let value = value.trim();
+            let invalid = || format!("invalid value for {}: {}", key.trim(), value);
+            match key.trim() {
+                "min_digits" => grammar.min_digits = value.parse().map_err(|_| invalid())?,
+                "max_digits" => grammar.max_digits = value.parse().map_err(|_| invalid())?,
+                "signed" => grammar.signed = value.parse().map_err(|_| invalid())?,
+                "whitespace" => grammar.whitespace = value.parse().map_err(|_| invalid())?,
+                "case_sensitive" => {
+                    grammar.case_sensitive = value.parse().map_err(|_| invalid())?
+                }
+                other => return Err(format!("unknown grammar key: {}", other)),
             }
         }
-        (Some(i), None) => (Some(i), "do()".len()),
-        (None, Some(j)) => (Some(j), "don't()".len()),
-        (None, None) => (None, 0),
+        grammar.validate()?;
+        Ok(grammar)
     }
 }
 
-fn challenge_02(mem: &Vec<String>) -> u64 {
-    let mut sum = 0u64;
+impl Grammar {
+    // Operands need at least one digit, and at most 18 so they fit an i64.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_digits == 0 || self.min_digits > self.max_digits {
+            return Err(format!(
+                "invalid digit limits: {}..{}",
+                self.min_digits, self.max_digits
+            ));
+        }
+        if self.max_digits > 18 {
+            return Err(format!("max_digits {} overflows i64", self.max_digits));
+        }
+        Ok(())
+    }
 
-    let mut enable = true;
+    pub fn from_file(path: &str) -> Result<Grammar, String> {
+        fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path, e))?
+            .parse()
+    }
+
+    fn is_space(&self, byte: u8) -> bool {
+        matches!(byte, b' ' | b'\t' | b'\n' | b'\r')
+    }
+
+    fn same_name(&self, a: &[u8], b: &[u8]) -> bool {
+        if self.case_sensitive {
+            a == b
+        } else {
+            a.eq_ignore_ascii_case(b)
+        }
+    }
+
+    fn has_prefix(&self, text: &[u8], prefix: &[u8]) -> bool {
+        text.len() >= prefix.len() && self.same_name(&text[..prefix.len()], prefix)
+    }
+
+    fn operand_pattern(&self) -> String {
+        let space = if self.whitespace { "[\t\n\r ]*" } else { "" };
+        format!(
+            "{space}({}[0-9]{{{},{}}}){space}",
+            if self.signed { "[+-]?" } else { "" },
+            self.min_digits,
+            self.max_digits,
+            space = space
+        )
+    }
+
+    fn pattern(&self, name: &str, arity: usize) -> String {
+        let name = if self.case_sensitive {
+            regex::escape(name)
+        } else {
+            // ASCII-only folding, like `same_name`; Unicode folding would also
+            // accept e.g. `ſ` for `s`.
+            format!("(?i-u:{})", regex::escape(name))
+        };
+        let operands = if arity == 0 && self.whitespace {
+            "[\t\n\r ]*".to_string()
+        } else {
+            vec![self.operand_pattern(); arity].join(",")
+        };
+        format!(r"({}\({}\))", name, operands)
+    }
+}
+
+// Contents of an instruction's parentheses, fed one byte at a time after
+// the opening bracket.
+struct OperandParser {
+    arity: usize,
+    operands: Vec<i64>,
+    negative: bool,
+    signed: bool,
+    current: Option<i64>,
+    digits: usize,
+    closed: bool,
+}
+
+enum OperandStep {
+    Continue,
+    Complete,
+    Fail(MissReason),
+}
+
+impl OperandParser {
+    fn new(arity: usize) -> OperandParser {
+        OperandParser {
+            arity,
+            operands: Vec::with_capacity(arity),
+            negative: false,
+            signed: false,
+            current: None,
+            digits: 0,
+            closed: false,
+        }
+    }
+
+    fn finish_operand(&mut self, grammar: &Grammar) -> Result<(), MissReason> {
+        match self.current.take() {
+            None if self.signed => Err(MissReason::TooFewDigits),
+            None => Err(MissReason::TooFewOperands),
+            Some(_) if self.digits < grammar.min_digits => Err(MissReason::TooFewDigits),
+            Some(value) => {
+                self.operands
+                    .push(if self.negative { -value } else { value });
+                self.negative = false;
+                self.signed = false;
+                self.digits = 0;
+                self.closed = false;
+                Ok(())
+            }
+        }
+    }
+
+    fn step(&mut self, byte: u8, grammar: &Grammar) -> OperandStep {
+        let fail = OperandStep::Fail;
+        match byte {
+            b'0'..=b'9' if self.operands.len() == self.arity => fail(MissReason::TooManyOperands),
+            b'0'..=b'9' if self.closed => fail(MissReason::Whitespace),
+            b'0'..=b'9' if self.digits == grammar.max_digits => fail(MissReason::TooManyDigits),
+            b'0'..=b'9' => {
+                self.current = Some(self.current.unwrap_or(0) * 10 + (byte - b'0') as i64);
+                self.digits += 1;
+                OperandStep::Continue
+            }
+            b'+' | b'-'
+                if grammar.signed
+                    && !self.signed
+                    && self.current.is_none()
+                    && self.operands.len() < self.arity =>
+            {
+                self.negative = byte == b'-';
+                self.signed = true;
+                OperandStep::Continue
+            }
+            b',' if self.current.is_none() && !self.signed => fail(MissReason::UnexpectedChar(',')),
+            b',' if self.operands.len() + 1 >= self.arity => fail(MissReason::TooManyOperands),
+            b',' => match self.finish_operand(grammar) {
+                Ok(()) => OperandStep::Continue,
+                Err(reason) => fail(reason),
+            },
+            b')' if self.arity == 0 => OperandStep::Complete,
+            b')' => match self.finish_operand(grammar) {
+                Ok(()) if self.operands.len() == self.arity => OperandStep::Complete,
+                Ok(()) => fail(MissReason::TooFewOperands),
+                Err(reason) => fail(reason),
+            },
+            b if grammar.whitespace && grammar.is_space(b) => {
+                if self.signed && self.current.is_none() {
+                    return fail(MissReason::Whitespace);
+                }
+                if self.current.is_some() {
+                    self.closed = true;
+                }
+                OperandStep::Continue
+            }
+            b if grammar.is_space(b) || b.is_ascii_whitespace() => fail(MissReason::Whitespace),
+            b']' | b'}' | b'>' => fail(MissReason::BadBracket(byte as char)),
+            _ => fail(MissReason::UnexpectedChar(byte as char)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Instruction {
+    op: usize,
+    operands: Vec<i64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Token {
+    instruction: Instruction,
+    span: Range<usize>,
+}
+
+struct InstructionSet {
+    operations: Vec<Box<dyn Operation>>,
+    grammar: Grammar,
+    // Capture group holding the whole instruction for each operation; its
+    // operands follow in the next `arity` groups.
+    groups: Vec<usize>,
+    // Compiled on first use, so the byte scanner never pays for it.
+    pattern: OnceCell<Regex>,
+}
+
+impl InstructionSet {
+    // Only used for the built-in sets, whose names are known to be distinct.
+    fn new(operations: Vec<Box<dyn Operation>>) -> InstructionSet {
+        InstructionSet::with_grammar(operations, Grammar::default()).unwrap()
+    }
+
+    fn with_grammar(
+        operations: Vec<Box<dyn Operation>>,
+        grammar: Grammar,
+    ) -> Result<InstructionSet, String> {
+        grammar.validate()?;
+        let mut groups = Vec::with_capacity(operations.len());
+        let mut group = 1;
+        for (idx, op) in operations.iter().enumerate() {
+            if operations[..idx]
+                .iter()
+                .any(|o| grammar.same_name(o.name().as_bytes(), op.name().as_bytes()))
+            {
+                return Err(format!("operation {} registered twice", op.name()));
+            }
+            groups.push(group);
+            group += 1 + op.arity();
+        }
+        Ok(InstructionSet {
+            operations,
+            grammar,
+            groups,
+            pattern: OnceCell::new(),
+        })
+    }
+
+    fn pattern(&self) -> &Regex {
+        self.pattern.get_or_init(|| {
+            let alternatives: Vec<String> = self
+                .operations
+                .iter()
+                .map(|op| self.grammar.pattern(op.name(), op.arity()))
+                .collect();
+            Regex::new(&alternatives.join("|")).unwrap()
+        })
+    }
+
+    fn part_1() -> InstructionSet {
+        InstructionSet::new(vec![Box::new(Mul)])
+    }
+
+    fn part_2() -> InstructionSet {
+        InstructionSet::new(vec![Box::new(Mul), Box::new(Do), Box::new(Dont)])
+    }
+
+    fn from_names(names: &[&str], grammar: &Grammar) -> Result<InstructionSet, String> {
+        names
+            .iter()
+            .map(|name| operation(name).ok_or(format!("unknown instruction: {}", name)))
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(|operations| InstructionSet::with_grammar(operations, grammar.clone()))
+    }
+
+    fn tokenize(&self, mem: &str) -> Vec<Token> {
+        if cfg!(feature = "handwritten-scanner") {
+            self.tokenize_bytes(mem)
+        } else {
+            self.tokenize_regex(mem)
+        }
+    }
+
+    fn tokenize_bytes(&self, mem: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        Scanner::new(self).feed(mem.as_bytes(), &mut tokens);
+        tokens
+    }
+
+    fn tokenize_regex(&self, mem: &str) -> Vec<Token> {
+        self.pattern()
+            .captures_iter(mem)
+            .filter_map(|caps| {
+                let op = self.groups.iter().position(|g| caps.get(*g).is_some())?;
+                let group = self.groups[op];
+                let operands: Vec<i64> = (1..=self.operations[op].arity())
+                    .map(|i| caps[group + i].parse().unwrap())
+                    .collect();
+                if !self.operations[op].validate(&operands) {
+                    return None;
+                }
+                Some(Token {
+                    instruction: Instruction { op, operands },
+                    span: caps.get(0).unwrap().range(),
+                })
+            })
+            .collect()
+    }
+
+    fn execute(&self, instruction: &Instruction, state: &mut State) {
+        self.operations[instruction.op].execute(&instruction.operands, state);
+    }
+
+    fn run(&self, mem: &str) -> State {
+        self.tokenize(mem)
+            .iter()
+            .fold(State::default(), |mut state, token| {
+                self.execute(&token.instruction, &mut state);
+                state
+            })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct TraceEntry {
+    offset: usize,
+    instruction: String,
+    enabled: bool,
+    sum: i64,
+}
+
+impl TraceEntry {
+    fn to_text(&self) -> String {
+        format!(
+            "{:>8}  {:<14} {:<8} {}",
+            self.offset,
+            self.instruction,
+            if self.enabled { "enabled" } else { "disabled" },
+            self.sum
+        )
+    }
+
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"offset\":{},\"instruction\":\"{}\",\"enabled\":{},\"sum\":{}}}",
+            self.offset,
+            json_escape(&self.instruction),
+            self.enabled,
+            self.sum
+        )
+    }
+}
+
+// Escapes a string for use inside JSON quotes; control characters would
+// otherwise break one-record-per-line framing.
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// `enabled` is the state the instruction was reached in, `sum` the
+// accumulator once it has been executed.
+fn trace(set: &InstructionSet, mem: &str) -> Result<Vec<TraceEntry>, String> {
+    let mut state = State::default();
+    set.tokenize(mem)
+        .into_iter()
+        .map(|token| {
+            let enabled = state.enabled;
+            set.execute(&token.instruction, &mut state);
+            if state.overflowed {
+                return Err(format!(
+                    "accumulator overflowed at offset {}",
+                    token.span.start
+                ));
+            }
+            Ok(TraceEntry {
+                offset: token.span.start,
+                instruction: mem[token.span].to_string(),
+                enabled,
+                sum: state.accumulator,
+            })
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MissReason {
+    BadBracket(char),
+    Whitespace,
+    TooFewDigits,
+    TooManyDigits,
+    UnexpectedChar(char),
+    TooFewOperands,
+    TooManyOperands,
+    Unterminated,
+    Rejected,
+}
+
+impl Display for MissReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MissReason::BadBracket(c) => write!(f, "bad bracket '{}'", c),
+            MissReason::Whitespace => write!(f, "whitespace"),
+            MissReason::TooFewDigits => write!(f, "too few digits"),
+            MissReason::TooManyDigits => write!(f, "too many digits"),
+            MissReason::UnexpectedChar(c) => write!(f, "unexpected character '{}'", c),
+            MissReason::TooFewOperands => write!(f, "too few operands"),
+            MissReason::TooManyOperands => write!(f, "too many operands"),
+            MissReason::Unterminated => write!(f, "unterminated"),
+            MissReason::Rejected => write!(f, "operands rejected"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct NearMiss {
+    offset: usize,
+    snippet: String,
+    reason: MissReason,
+}
+
+// Parses the call whose name ends at `pos`, returning where it failed and why.
+fn parse_call(
+    mem: &str,
+    pos: usize,
+    op: &dyn Operation,
+    grammar: &Grammar,
+) -> Result<(), (usize, MissReason)> {
+    let bytes = mem.as_bytes();
+    let next_char = |i: usize| mem[i..].chars().next().unwrap();
+    let fail = |i: usize, reason: MissReason| {
+        let reason = match reason {
+            MissReason::UnexpectedChar(_) => MissReason::UnexpectedChar(next_char(i)),
+            MissReason::BadBracket(_) => MissReason::BadBracket(next_char(i)),
+            reason => reason,
+        };
+        Err((i + next_char(i).len_utf8(), reason))
+    };
+    match bytes[pos] {
+        b'(' => (),
+        b if b.is_ascii_whitespace() => return fail(pos, MissReason::Whitespace),
+        _ => return fail(pos, MissReason::BadBracket(' ')),
+    }
+    let mut parser = OperandParser::new(op.arity());
+    for (i, &byte) in bytes.iter().enumerate().skip(pos + 1) {
+        match parser.step(byte, grammar) {
+            OperandStep::Continue => (),
+            OperandStep::Complete if op.validate(&parser.operands) => return Ok(()),
+            OperandStep::Complete => return fail(i, MissReason::Rejected),
+            OperandStep::Fail(reason) => return fail(i, reason),
+        }
+    }
+    Err((bytes.len(), MissReason::Unterminated))
+}
+
+// Every occurrence of an instruction name followed by an opening bracket or
+// whitespace that does not parse as an instruction. Names merely embedded in
+// other words (`do_not_mul`, the `do` in `don't`) are not reported.
+fn diagnose(set: &InstructionSet, mem: &str) -> Vec<NearMiss> {
+    let bytes = mem.as_bytes();
+    let mut misses = Vec::new();
+    for offset in 0..bytes.len() {
+        for op in set.operations.iter() {
+            if !set
+                .grammar
+                .has_prefix(&bytes[offset..], op.name().as_bytes())
+            {
+                continue;
+            }
+            let after = offset + op.name().len();
+            match bytes.get(after) {
+                Some(b) if b"([{<".contains(b) || b.is_ascii_whitespace() => (),
+                _ => continue,
+            }
+            if let Err((end, reason)) = parse_call(mem, after, op.as_ref(), &set.grammar) {
+                misses.push(NearMiss {
+                    offset,
+                    snippet: mem[offset..end].to_string(),
+                    reason,
+                });
+            }
+        }
+    }
+    misses
+}
+
+// Recursive variant of the grammar where an operand may itself be an
+// instruction with a value, nested at most `max_depth` levels deep.
+fn parse_nested(
+    set: &InstructionSet,
+    mem: &[u8],
+    pos: usize,
+    depth: usize,
+    max_depth: usize,
+) -> Option<(Instruction, usize)> {
+    set.operations
+        .iter()
+        .enumerate()
+        .filter(|(_, o)| set.grammar.has_prefix(&mem[pos..], o.name().as_bytes()))
+        .find_map(|(op, _)| parse_nested_operands(set, op, mem, pos, depth, max_depth))
+}
+
+fn parse_nested_operands(
+    set: &InstructionSet,
+    op: usize,
+    mem: &[u8],
+    pos: usize,
+    depth: usize,
+    max_depth: usize,
+) -> Option<(Instruction, usize)> {
+    let grammar = &set.grammar;
+    let skip_space = |mut i: usize| {
+        while grammar.whitespace && i < mem.len() && grammar.is_space(mem[i]) {
+            i += 1;
+        }
+        i
+    };
+    let operation = &set.operations[op];
+    let mut i = pos + operation.name().len();
+    if mem.get(i) != Some(&b'(') {
+        return None;
+    }
+    i = skip_space(i + 1);
+    let mut operands = Vec::with_capacity(operation.arity());
+    for n in 0..operation.arity() {
+        if n > 0 {
+            if mem.get(i) != Some(&b',') {
+                return None;
+            }
+            i = skip_space(i + 1);
+        }
+        let sign = match mem.get(i) {
+            Some(b'+' | b'-') if grammar.signed => 1,
+            _ => 0,
+        };
+        let digits = mem[i + sign..]
+            .iter()
+            .take(grammar.max_digits + 1)
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if digits > grammar.max_digits || (digits > 0 && digits < grammar.min_digits) {
+            return None;
+        } else if digits > 0 {
+            operands.push(
+                std::str::from_utf8(&mem[i..i + sign + digits])
+                    .ok()?
+                    .parse()
+                    .ok()?,
+            );
+            i += sign + digits;
+        } else if sign == 0 && depth < max_depth {
+            let (inner, end) = parse_nested(set, mem, i, depth + 1, max_depth)?;
+            operands.push(set.operations[inner.op].value(&inner.operands)?);
+            i = end;
+        } else {
+            return None;
+        }
+        i = skip_space(i);
+    }
+    if mem.get(i) != Some(&b')') || !operation.validate(&operands) {
+        return None;
+    }
+    Some((Instruction { op, operands }, i + 1))
+}
 
-    for unit in mem {
-        let mut idx = 0;
-        while idx < unit.len() {
-            let (bloc, offset) = next_instruction(&unit[idx..]);
-            match bloc {
-                Some(pos) => {
-                    if enable {
-                        sum += challenge_01(&unit[idx..idx + pos]);
+fn tokenize_nested(set: &InstructionSet, mem: &str, max_depth: usize) -> Vec<Token> {
+    let bytes = mem.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match parse_nested(set, bytes, pos, 0, max_depth) {
+            Some((instruction, end)) => {
+                tokens.push(Token {
+                    instruction,
+                    span: pos..end,
+                });
+                pos = end;
+            }
+            None => pos += 1,
+        }
+    }
+    tokens
+}
+
+fn run_nested(set: &InstructionSet, mem: &str, max_depth: usize) -> State {
+    tokenize_nested(set, mem, max_depth)
+        .iter()
+        .fold(State::default(), |mut state, token| {
+            set.execute(&token.instruction, &mut state);
+            state
+        })
+}
+
+enum ScanState {
+    Name,
+    Operands { op: usize, parser: OperandParser },
+}
+
+enum Step {
+    Continue,
+    Complete,
+    Fail,
+}
+
+// Byte-at-a-time recogniser for the same grammar as `tokenize_regex`.
+// `pending` holds the bytes of the attempt that started at `start`; when it
+// fails, scanning resumes one byte after `start` by replaying them, which
+// keeps the leftmost-first semantics of the regex across chunk boundaries.
+struct Scanner<'a> {
+    set: &'a InstructionSet,
+    starts: [bool; 256],
+    state: ScanState,
+    pending: Vec<u8>,
+    start: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(set: &'a InstructionSet) -> Scanner<'a> {
+        let mut starts = [false; 256];
+        for op in set.operations.iter() {
+            if let Some(&first) = op.name().as_bytes().first() {
+                starts[first as usize] = true;
+                if !set.grammar.case_sensitive {
+                    starts[first.to_ascii_lowercase() as usize] = true;
+                    starts[first.to_ascii_uppercase() as usize] = true;
+                }
+            }
+        }
+        Scanner {
+            set,
+            starts,
+            state: ScanState::Name,
+            pending: Vec::new(),
+            start: 0,
+        }
+    }
+
+    fn feed(&mut self, bytes: &[u8], tokens: &mut Vec<Token>) {
+        let mut i = 0;
+        while i < bytes.len() {
+            if self.pending.is_empty() {
+                // Fast path: skip everything that cannot start an instruction.
+                match bytes[i..].iter().position(|b| self.starts[*b as usize]) {
+                    Some(skip) => {
+                        self.start += skip;
+                        i += skip;
+                    }
+                    None => {
+                        self.start += bytes.len() - i;
+                        return;
                     }
-                    idx = idx + pos + offset;
-                    enable = if offset == "do()".len() { true } else { false };
                 }
-                None => {
-                    if enable {
-                        sum += challenge_01(&unit[idx..]);
+            }
+            self.push(bytes[i], tokens);
+            i += 1;
+        }
+    }
+
+    fn push(&mut self, byte: u8, tokens: &mut Vec<Token>) {
+        self.pending.push(byte);
+        match self.step(byte) {
+            Step::Continue => (),
+            Step::Complete => {
+                let end = self.start + self.pending.len();
+                if let ScanState::Operands { op, parser } =
+                    mem::replace(&mut self.state, ScanState::Name)
+                {
+                    if self.set.operations[op].validate(&parser.operands) {
+                        tokens.push(Token {
+                            instruction: Instruction {
+                                op,
+                                operands: parser.operands,
+                            },
+                            span: self.start..end,
+                        });
+                    }
+                }
+                self.pending.clear();
+                self.start = end;
+            }
+            Step::Fail => {
+                self.state = ScanState::Name;
+                self.start += 1;
+                if self.pending.len() == 1 {
+                    self.pending.clear();
+                } else {
+                    let retry = mem::take(&mut self.pending);
+                    for &b in &retry[1..] {
+                        self.push(b, tokens);
                     }
-                    idx = unit.len();
                 }
             }
         }
     }
 
-    sum
+    fn step(&mut self, byte: u8) -> Step {
+        let grammar = &self.set.grammar;
+        match &mut self.state {
+            ScanState::Name => {
+                let (name, last) = self.pending.split_at(self.pending.len() - 1);
+                if last[0] == b'(' {
+                    if let Some(op) = self
+                        .set
+                        .operations
+                        .iter()
+                        .position(|o| grammar.same_name(o.name().as_bytes(), name))
+                    {
+                        self.state = ScanState::Operands {
+                            op,
+                            parser: OperandParser::new(self.set.operations[op].arity()),
+                        };
+                        return Step::Continue;
+                    }
+                }
+                if self
+                    .set
+                    .operations
+                    .iter()
+                    .any(|o| grammar.has_prefix(o.name().as_bytes(), &self.pending))
+                {
+                    Step::Continue
+                } else {
+                    Step::Fail
+                }
+            }
+            ScanState::Operands { parser, .. } => match parser.step(byte, grammar) {
+                OperandStep::Continue => Step::Continue,
+                OperandStep::Complete => Step::Complete,
+                OperandStep::Fail(_) => Step::Fail,
+            },
+        }
+    }
+}
+
+// Resumable interpreter: chunks can be fed as they arrive, instructions split
+// across chunk boundaries are completed by the next call to `feed`.
+struct StreamInterpreter<'a> {
+    scanner: Scanner<'a>,
+    tokens: Vec<Token>,
+    state: State,
+}
+
+impl<'a> StreamInterpreter<'a> {
+    fn new(set: &'a InstructionSet) -> StreamInterpreter<'a> {
+        StreamInterpreter {
+            scanner: Scanner::new(set),
+            tokens: Vec::new(),
+            state: State::default(),
+        }
+    }
+
+    fn feed(&mut self, chunk: &[u8]) {
+        self.scanner.feed(chunk, &mut self.tokens);
+        for token in self.tokens.drain(..) {
+            self.scanner
+                .set
+                .execute(&token.instruction, &mut self.state);
+        }
+    }
+
+    fn run<R: Read>(mut self, mut reader: R, chunk_size: usize) -> io::Result<State> {
+        let mut chunk = vec![0u8; chunk_size.max(1)];
+        loop {
+            let read = reader.read(&mut chunk)?;
+            if read == 0 {
+                return Ok(self.state);
+            }
+            self.feed(&chunk[..read]);
+        }
+    }
+}
+
+fn challenge_01(mem: &str) -> u64 {
+    InstructionSet::part_1().run(mem).accumulator as u64
+}
+
+fn challenge_02(mem: &str) -> u64 {
+    InstructionSet::part_2().run(mem).accumulator as u64
+}
+
+pub fn stream_parts(chunk_size: usize) -> io::Result<(u64, u64)> {
+    let part_1 = StreamInterpreter::new(&InstructionSet::part_1())
+        .run(File::open("data/day03_01.txt")?, chunk_size)?;
+    let part_2 = StreamInterpreter::new(&InstructionSet::part_2())
+        .run(File::open("data/day03_01.txt")?, chunk_size)?;
+    let result = |state: State| {
+        state
+            .result()
+            .map(|sum| sum as u64)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    };
+    Ok((result(part_1)?, result(part_2)?))
+}
+
+pub fn benchmark_scanners(iterations: usize) -> (Duration, Duration) {
+    let mem = get_data();
+    let set = InstructionSet::part_2();
+    // Compile the regex up front so only the scanning is timed.
+    set.pattern();
+    let time = |tokenize: &dyn Fn(&str) -> Vec<Token>| {
+        let start = Instant::now();
+        for _ in 0..iterations {
+            assert!(!tokenize(&mem).is_empty());
+        }
+        start.elapsed()
+    };
+    (
+        time(&|mem| set.tokenize_regex(mem)),
+        time(&|mem| set.tokenize_bytes(mem)),
+    )
+}
+
+pub fn run_instruction_set(names: &[&str], grammar: &Grammar) -> Result<i64, String> {
+    InstructionSet::from_names(names, grammar)?
+        .run(&get_data())
+        .result()
+}
+
+pub fn nested_instruction_set(
+    names: &[&str],
+    grammar: &Grammar,
+    max_depth: usize,
+) -> Result<i64, String> {
+    let set = InstructionSet::from_names(names, grammar)?;
+    run_nested(&set, &get_data(), max_depth).result()
+}
+
+pub fn diagnose_instruction_set(names: &[&str], grammar: &Grammar) -> Result<String, String> {
+    let set = InstructionSet::from_names(names, grammar)?;
+    Ok(diagnose(&set, &get_data())
+        .iter()
+        .map(|miss| format!("{:>8}  {:<16} {}", miss.offset, miss.snippet, miss.reason))
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+pub fn trace_instruction_set(
+    names: &[&str],
+    grammar: &Grammar,
+    format: &str,
+) -> Result<String, String> {
+    let render = match format {
+        "text" => TraceEntry::to_text,
+        "jsonl" => TraceEntry::to_json,
+        _ => return Err(format!("unknown trace format: {}", format)),
+    };
+    let set = InstructionSet::from_names(names, grammar)?;
+    let lines: Vec<String> = trace(&set, &get_data())?.iter().map(render).collect();
+    Ok(lines.join("\n"))
 }
 
 pub fn part_1() -> u64 {
-    get_data().iter().map(|x| challenge_01(x)).sum()
+    challenge_01(&get_data())
 }
 
 pub fn part_2() -> u64 {
     challenge_02(&get_data())
 }
 
-fn get_data() -> Vec<String> {
-    let mut mem: Vec<String> = Vec::with_capacity(6);
-    for line in fs::read_to_string("data/day03_01.txt").unwrap().lines() {
-        mem.push(String::from(line));
-    }
-    mem
+fn get_data() -> String {
+    fs::read_to_string("data/day03_01.txt").unwrap()
 }
 
 #[cfg(test)]
@@ -87,37 +1081,442 @@ mod test {
         assert_eq!(actual, 161);
     }
 
+    fn token(op: usize, operands: &[i64], span: Range<usize>) -> Token {
+        Token {
+            instruction: Instruction {
+                op,
+                operands: operands.to_vec(),
+            },
+            span,
+        }
+    }
+
     #[test]
-    fn check_next_instruction_dont() {
+    fn check_tokenize() {
         // Arrange
         let mem = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
-        let expected = (mem.find("don't()"), "don't()".len());
+        let expected = vec![
+            token(0, &[2, 4], 1..9),
+            token(2, &[], 20..27),
+            token(0, &[5, 5], 28..36),
+            token(0, &[11, 8], 48..57),
+            token(1, &[], 59..63),
+            token(0, &[8, 5], 64..72),
+        ];
         // Act
-        let actual = next_instruction(mem);
+        let actual = InstructionSet::part_2().tokenize(mem);
         // Assert
         assert_eq!(actual, expected);
     }
 
     #[test]
-    fn check_next_instruction_do() {
+    fn check_tokenize_spans() {
         // Arrange
-        let mem = "_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
-        let expected = (mem.find("do()"), "do()".len());
+        let mem = "don't()do()mul(1,999)mul(1000,1)";
         // Act
-        let actual = next_instruction(mem);
+        let actual = InstructionSet::part_2().tokenize(mem);
         // Assert
-        assert_eq!(actual, expected);
+        assert_eq!(
+            actual
+                .iter()
+                .map(|t| &mem[t.span.clone()])
+                .collect::<Vec<&str>>(),
+            vec!["don't()", "do()", "mul(1,999)"]
+        );
+    }
+
+    #[test]
+    fn check_extended_instruction_set() {
+        // Arrange
+        let set =
+            InstructionSet::from_names(&["mul", "add", "sub", "div", "reset"], &Grammar::default())
+                .unwrap();
+        let mem = "mul(2,3)add(4,5)div(7,0)sub(1,9)reset()xadd(1,1)div(9,2)do()";
+        // Act
+        let actual = set.run(mem);
+        // Assert
+        assert_eq!(actual.accumulator, (1 + 1) + 9 / 2);
+        assert!(InstructionSet::from_names(&["mul", "pow"], &Grammar::default()).is_err());
+        assert_eq!(
+            InstructionSet::from_names(&["mul", "mul"], &Grammar::default()).err(),
+            Some("operation mul registered twice".to_string())
+        );
     }
 
     #[test]
     fn check_challenge_02() {
         // Arrange
-        let mem = vec![String::from(
-            "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))",
-        )];
+        let mem = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
         // Act
-        let actual = challenge_02(&mem);
+        let actual = challenge_02(mem);
         // Assert
         assert_eq!(actual, 48);
     }
+
+    #[test]
+    fn check_stream_chunk_boundaries() {
+        // Arrange
+        let mem = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        let set = InstructionSet::part_2();
+        for chunk_size in 1..=mem.len() {
+            // Act
+            let actual = StreamInterpreter::new(&set)
+                .run(mem.as_bytes(), chunk_size)
+                .unwrap();
+            // Assert
+            assert_eq!(actual.accumulator, 48, "chunk size {}", chunk_size);
+        }
+    }
+
+    #[test]
+    fn check_scanner_matches_regex() {
+        // Arrange
+        let mem = get_data() + "mmul(1,2)mul(1,2,3)mul(4,5)mul(,1)mul(1234,5)don't(do()";
+        let set = InstructionSet::from_names(
+            &["mul", "do", "don't", "div", "reset"],
+            &Grammar::default(),
+        )
+        .unwrap();
+        // Act
+        let actual = set.tokenize_bytes(&mem);
+        // Assert
+        assert_eq!(actual, set.tokenize_regex(&mem));
+    }
+
+    #[test]
+    fn check_stream_matches_in_memory() {
+        // Arrange
+        let expected = (part_1(), part_2());
+        for chunk_size in [1, 7, 64, 4096] {
+            // Act
+            let actual = stream_parts(chunk_size).unwrap();
+            // Assert
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn check_trace() {
+        // Arrange
+        let mem = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        // Act
+        let actual = trace(&InstructionSet::part_2(), mem).unwrap();
+        // Assert
+        assert_eq!(
+            actual
+                .iter()
+                .map(|e| (e.offset, e.instruction.as_str(), e.enabled, e.sum))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, "mul(2,4)", true, 8),
+                (20, "don't()", true, 8),
+                (28, "mul(5,5)", false, 8),
+                (48, "mul(11,8)", false, 8),
+                (59, "do()", false, 8),
+                (64, "mul(8,5)", true, 48),
+            ]
+        );
+        assert_eq!(
+            actual[1].to_json(),
+            "{\"offset\":20,\"instruction\":\"don't()\",\"enabled\":true,\"sum\":8}"
+        );
+        assert_eq!(actual[2].to_text(), "      28  mul(5,5)       disabled 8");
+    }
+
+    #[test]
+    fn check_trace_json_escaping() {
+        // Arrange
+        let grammar = Grammar {
+            whitespace: true,
+            ..Grammar::default()
+        };
+        let set = InstructionSet::from_names(&["mul"], &grammar).unwrap();
+        // Act
+        let actual = trace(&set, "mul(\n2,\t3\r)").unwrap();
+        // Assert
+        assert_eq!(
+            actual[0].to_json(),
+            "{\"offset\":0,\"instruction\":\"mul(\\n2,\\t3\\r)\",\"enabled\":true,\"sum\":6}"
+        );
+        assert_eq!(json_escape("a\"b\\c\u{1}"), "a\\\"b\\\\c\\u0001");
+    }
+
+    #[test]
+    fn check_diagnose() {
+        // Arrange
+        let mem = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))\
+                   mul ( 2 , 4 )mul(1234,5)mul(1,2,3)don't(x)do(mul(4,";
+        let set = InstructionSet::part_2();
+        // Act
+        let actual = diagnose(&set, mem);
+        // Assert
+        assert_eq!(
+            actual
+                .iter()
+                .map(|m| (m.offset, m.snippet.as_str(), m.reason))
+                .collect::<Vec<_>>(),
+            vec![
+                (11, "mul[", MissReason::BadBracket('[')),
+                (38, "mul(32,64]", MissReason::BadBracket(']')),
+                (71, "mul ", MissReason::Whitespace),
+                (84, "mul(1234", MissReason::TooManyDigits),
+                (95, "mul(1,2,", MissReason::TooManyOperands),
+                (105, "don't(x", MissReason::UnexpectedChar('x')),
+                (113, "do(m", MissReason::UnexpectedChar('m')),
+                (116, "mul(4,", MissReason::Unterminated),
+            ]
+        );
+        assert!(actual
+            .iter()
+            .all(|m| mem[m.offset..].starts_with(&m.snippet)));
+    }
+
+    #[test]
+    fn check_diagnose_rejected() {
+        // Arrange
+        let set = InstructionSet::from_names(&["div", "mul"], &Grammar::default()).unwrap();
+        // Act
+        let actual = diagnose(&set, "div(4,0)mul(1,)mul()");
+        // Assert
+        assert_eq!(
+            actual.iter().map(|m| m.reason).collect::<Vec<_>>(),
+            vec![
+                MissReason::Rejected,
+                MissReason::TooFewOperands,
+                MissReason::TooFewOperands
+            ]
+        );
+    }
+
+    #[test]
+    fn check_nested() {
+        // Arrange
+        let set = InstructionSet::from_names(&["mul", "add", "do", "don't"], &Grammar::default())
+            .unwrap();
+        let mem = "mul(mul(2,3),4)xmul(add(1,2),5)don't()mul(9,9)do()mul(2,mul(3,mul(4,5)))";
+        // Act and Assert
+        assert_eq!(run_nested(&set, mem, 0).accumulator, 6 + 3 + 20);
+        assert_eq!(run_nested(&set, mem, 1).accumulator, 24 + 15 + 60);
+        assert_eq!(run_nested(&set, mem, 2).accumulator, 24 + 15 + 120);
+        assert_eq!(
+            run_nested(&set, "mul(do(),2)add(mul(1,2,3),4)", 3).accumulator,
+            0
+        );
+    }
+
+    #[test]
+    fn check_overflow() {
+        // Arrange
+        let grammar = Grammar {
+            max_digits: 18,
+            ..Grammar::default()
+        };
+        let set = InstructionSet::from_names(&["mul", "add"], &grammar).unwrap();
+        let too_large = "mul(999999999999999999,999999999999999999)add(1,2)";
+        let near_max = "mul(3037000499,3037000499)add(1,2)mul(3037000499,3037000499)";
+        // Act
+        let rejected = set.run(too_large);
+        let overflowed = set.run(near_max);
+        // Assert
+        assert_eq!(set.tokenize_regex(too_large).len(), 1);
+        assert_eq!(set.tokenize_bytes(too_large).len(), 1);
+        assert_eq!(rejected.result(), Ok(3));
+        assert!(overflowed.overflowed);
+        assert_eq!(overflowed.accumulator, 3037000499 * 3037000499 + 3);
+        assert_eq!(
+            overflowed.result(),
+            Err("accumulator overflowed".to_string())
+        );
+        assert_eq!(
+            trace(&set, near_max).err(),
+            Some("accumulator overflowed at offset 34".to_string())
+        );
+    }
+
+    #[test]
+    fn check_nested_overflow() {
+        // Arrange
+        let set = InstructionSet::part_1();
+        let mem = (0..7).fold("mul(999,999)".to_string(), |inner, _| {
+            format!("mul(999,{})", inner)
+        });
+        // Act
+        let actual = tokenize_nested(&set, &mem, 7);
+        // Assert
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].span.start, 3 * 8);
+        assert_eq!(run_nested(&set, &mem, 7).accumulator, 999i64.pow(6));
+        assert!(!Div.validate(&[i64::MIN, -1]));
+    }
+
+    #[test]
+    fn check_nested_flat_equivalence() {
+        // Arrange
+        let mem = get_data();
+        let set = InstructionSet::part_2();
+        // Act
+        let actual = tokenize_nested(&set, &mem, 0);
+        // Assert
+        assert_eq!(actual, set.tokenize(&mem));
+    }
+
+    // xorshift64, enough to generate reproducible corrupted memory
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+    }
+
+    #[test]
+    fn check_scanners_on_random_memory() {
+        // Arrange
+        let fragments = [
+            "mul(", "do()", "don't()", "do", "(", ")", ",", "1", "23", "456", "7890", "m", "u",
+            "l", "n't", " ", "x", "add(", "div(", "0", "reset()", "é",
+        ];
+        let set = InstructionSet::from_names(
+            &["mul", "do", "don't", "add", "div", "reset"],
+            &Grammar::default(),
+        )
+        .unwrap();
+        let mut rng = Rng(0x2024_0003);
+        for _ in 0..2000 {
+            let mem: String = (0..rng.below(60))
+                .map(|_| fragments[rng.below(fragments.len())])
+                .collect();
+            // Act
+            let actual = set.tokenize_bytes(&mem);
+            // Assert
+            assert_eq!(actual, set.tokenize_regex(&mem), "{}", mem);
+        }
+    }
+
+    #[test]
+    fn check_grammar_config() {
+        // Arrange
+        let config = "# recovered memory\nmax_digits = 4\nsigned = true\n\
+                      whitespace = true\ncase_sensitive = false\n";
+        // Act
+        let actual: Grammar = config.parse().unwrap();
+        // Assert
+        assert_eq!(
+            actual,
+            Grammar {
+                min_digits: 1,
+                max_digits: 4,
+                signed: true,
+                whitespace: true,
+                case_sensitive: false
+            }
+        );
+        assert_eq!("".parse::<Grammar>().unwrap(), Grammar::default());
+        assert!("min_digits = 4".parse::<Grammar>().is_err());
+        assert!("max_digits = 19".parse::<Grammar>().is_err());
+        assert!("colour = blue".parse::<Grammar>().is_err());
+        assert!("signed: yes".parse::<Grammar>().is_err());
+    }
+
+    #[test]
+    fn check_regex_is_compiled_lazily() {
+        // Arrange
+        let set = InstructionSet::part_2();
+        let mem = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)";
+        // Act
+        let bytes = set.tokenize_bytes(mem);
+        let compiled_by_bytes = set.pattern.get().is_some();
+        let regex = set.tokenize_regex(mem);
+        // Assert
+        assert!(!compiled_by_bytes);
+        assert!(set.pattern.get().is_some());
+        assert_eq!(bytes, regex);
+    }
+
+    #[test]
+    fn check_invalid_grammar_is_rejected() {
+        // Arrange
+        let empty = Grammar {
+            min_digits: 0,
+            ..Grammar::default()
+        };
+        let wide = Grammar {
+            max_digits: 19,
+            ..Grammar::default()
+        };
+        // Act and Assert
+        assert!(InstructionSet::from_names(&["mul"], &empty).is_err());
+        assert!(InstructionSet::from_names(&["mul"], &wide).is_err());
+        assert!(run_instruction_set(&["mul"], &empty).is_err());
+        assert!(nested_instruction_set(&["mul"], &wide, 2).is_err());
+    }
+
+    #[test]
+    fn check_custom_grammar() {
+        // Arrange
+        let grammar: Grammar = "max_digits = 4\nsigned = true\nwhitespace = true\n\
+                                case_sensitive = false"
+            .parse()
+            .unwrap();
+        let set = InstructionSet::from_names(&["mul", "do", "don't"], &grammar).unwrap();
+        let mem = "MUL( 2 , -4 )mul(1000,+2)Don't( )mul(3,3)DO()mul (1,1)mul(- 1,2)mul(1 2,3)";
+        // Act
+        let actual = set.run(mem);
+        // Assert
+        assert_eq!(actual.accumulator, -8 + 1000 * 2);
+        assert_eq!(set.tokenize_bytes(mem), set.tokenize_regex(mem));
+        assert_eq!(
+            diagnose(&set, mem)
+                .iter()
+                .map(|m| m.reason)
+                .collect::<Vec<_>>(),
+            vec![
+                MissReason::Whitespace,
+                MissReason::Whitespace,
+                MissReason::Whitespace
+            ]
+        );
+        assert_eq!(tokenize_nested(&set, mem, 0), set.tokenize_regex(mem));
+    }
+
+    #[test]
+    fn check_scanners_on_random_memory_with_grammar() {
+        // Arrange
+        let fragments = [
+            "mul(", "MuL(", "do()", "DON'T()", "(", ")", ",", " ", "\t", "-", "+", "1", "23",
+            "4567", "8", "x", "é", "sub(", "ſub(", "ſ", "\u{212A}",
+        ];
+        let grammar = Grammar {
+            min_digits: 2,
+            max_digits: 4,
+            signed: true,
+            whitespace: true,
+            case_sensitive: false,
+        };
+        let set = InstructionSet::from_names(&["mul", "do", "don't", "sub"], &grammar).unwrap();
+        let mut rng = Rng(0x2024_0039);
+        for _ in 0..2000 {
+            let mem: String = (0..rng.below(60))
+                .map(|_| fragments[rng.below(fragments.len())])
+                .collect();
+            // Act
+            let actual = set.tokenize_bytes(&mem);
+            // Assert
+            assert_eq!(actual, set.tokenize_regex(&mem), "{:?}", mem);
+            assert_eq!(tokenize_nested(&set, &mem, 0), actual, "{:?}", mem);
+        }
+        // Case folding stays ASCII-only: `ſ` (long s) is not an `s`.
+        for mem in ["ſub(12,34)", "SUB(12,34)"] {
+            assert_eq!(
+                set.tokenize_regex(mem),
+                set.tokenize_bytes(mem),
+                "{:?}",
+                mem
+            );
+        }
+        assert!(set.tokenize_regex("ſub(12,34)").is_empty());
+    }
 }
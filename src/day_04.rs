@@ -1,139 +1,925 @@
+use std::collections::{HashMap, VecDeque};
 use std::fs;
+use std::io;
 
-fn challenge_01(text: &Vec<Vec<char>>) -> u64 {
-    text.iter()
+use num::Integer;
+use rayon::prelude::*;
+
+type Grid = Vec<Vec<char>>;
+
+#[derive(Hash, Eq, PartialEq, Debug, Copy, Clone)]
+enum Direction {
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+    Up,
+    UpRight,
+}
+
+const EIGHT_WAY: [Direction; 8] = [
+    Direction::Right,
+    Direction::DownRight,
+    Direction::Down,
+    Direction::DownLeft,
+    Direction::Left,
+    Direction::UpLeft,
+    Direction::Up,
+    Direction::UpRight,
+];
+
+const FOUR_WAY: [Direction; 4] = [
+    Direction::Right,
+    Direction::Down,
+    Direction::Left,
+    Direction::Up,
+];
+
+// One direction out of each opposite pair: words are never read backwards.
+const FORWARD: [Direction; 4] = [
+    Direction::Right,
+    Direction::DownRight,
+    Direction::Down,
+    Direction::UpRight,
+];
+
+fn direction_set(name: &str) -> Option<&'static [Direction]> {
+    match name {
+        "eight" => Some(&EIGHT_WAY),
+        "four" => Some(&FOUR_WAY),
+        "forward" => Some(&FORWARD),
+        _ => None,
+    }
+}
+
+impl Direction {
+    fn delta(&self) -> (isize, isize) {
+        match self {
+            Direction::Right => (1, 0),
+            Direction::DownRight => (1, 1),
+            Direction::Down => (0, 1),
+            Direction::DownLeft => (-1, 1),
+            Direction::Left => (-1, 0),
+            Direction::UpLeft => (-1, -1),
+            Direction::Up => (0, -1),
+            Direction::UpRight => (1, -1),
+        }
+    }
+
+    fn opposite(&self) -> Direction {
+        match self {
+            Direction::Right => Direction::Left,
+            Direction::DownRight => Direction::UpLeft,
+            Direction::Down => Direction::Up,
+            Direction::DownLeft => Direction::UpRight,
+            Direction::Left => Direction::Right,
+            Direction::UpLeft => Direction::DownRight,
+            Direction::Up => Direction::Down,
+            Direction::UpRight => Direction::DownLeft,
+        }
+    }
+}
+
+#[derive(Hash, Eq, PartialEq, Debug, Copy, Clone)]
+struct Position {
+    x: usize,
+    y: usize,
+}
+
+#[derive(Hash, Eq, PartialEq, Debug, Copy, Clone)]
+struct Match {
+    start: Position,
+    direction: Direction,
+}
+
+fn walk(grid: &Grid, pos: Position, direction: Direction, steps: usize) -> Option<Position> {
+    let (dx, dy) = direction.delta();
+    let x = pos.x.checked_add_signed(dx * steps as isize)?;
+    let y = pos.y.checked_add_signed(dy * steps as isize)?;
+    if y < grid.len() && x < grid[y].len() {
+        Some(Position { x, y })
+    } else {
+        None
+    }
+}
+
+// Like `walk`, but leaving the grid on one edge re-enters it on the opposite one.
+// The grid must be rectangular.
+fn wrap(grid: &Grid, pos: Position, direction: Direction, steps: usize) -> Option<Position> {
+    let (dx, dy) = direction.delta();
+    let width = grid[0].len() as isize;
+    let height = grid.len() as isize;
+    let x = (pos.x as isize + dx * steps as isize).rem_euclid(width);
+    let y = (pos.y as isize + dy * steps as isize).rem_euclid(height);
+    Some(Position {
+        x: x as usize,
+        y: y as usize,
+    })
+}
+
+type Walker = fn(&Grid, Position, Direction, usize) -> Option<Position>;
+
+fn matches_at(
+    grid: &Grid,
+    word: &[char],
+    start: Position,
+    direction: Direction,
+    walker: Walker,
+) -> bool {
+    word.iter()
         .enumerate()
-        .map(|(y, line)| {
-            line.iter()
-                .enumerate()
-                .map(|(x, _)| find_xmas(text, x, y))
-                .sum::<u64>()
-        })
-        .sum()
+        .all(|(i, c)| walker(grid, start, direction, i).is_some_and(|pos| grid[pos.y][pos.x] == *c))
+}
+
+fn search_with(grid: &Grid, word: &str, directions: &[Direction], walker: Walker) -> Vec<Match> {
+    let word: Vec<char> = word.chars().collect();
+    let mut matches = Vec::new();
+    if word.is_empty() {
+        return matches;
+    }
+    for (y, line) in grid.iter().enumerate() {
+        for (x, c) in line.iter().enumerate() {
+            if *c != word[0] {
+                continue;
+            }
+            let start = Position { x, y };
+            for direction in directions {
+                if matches_at(grid, &word, start, *direction, walker) {
+                    matches.push(Match {
+                        start,
+                        direction: *direction,
+                    });
+                }
+            }
+        }
+    }
+    matches
 }
 
-fn find_xmas(text: &Vec<Vec<char>>, x: usize, y: usize) -> u64 {
-    let checkers = vec![
-        check_rl, check_lr, check_dr, check_ur, check_dl, check_ul, check_d, check_u,
-    ];
-    checkers
+fn word_search(grid: &Grid, word: &str, directions: &[Direction]) -> Vec<Match> {
+    search_with(grid, word, directions, walk)
+}
+
+// Number of steps before a wrapping walk returns to its starting cell.
+fn cycle_length(width: usize, height: usize, direction: Direction) -> usize {
+    match direction.delta() {
+        (0, _) => height,
+        (_, 0) => width,
+        _ => width.lcm(&height),
+    }
+}
+
+// Words may run off one edge and continue from the opposite one. Directions
+// whose cycle is shorter than the word would read cells twice, so the word is
+// only searched along the others.
+fn wrapping_search(
+    grid: &Grid,
+    word: &str,
+    directions: &[Direction],
+) -> Result<Vec<Match>, String> {
+    if grid.is_empty() || grid.iter().any(|line| line.len() != grid[0].len()) {
+        return Err("wrapping search needs a non-empty rectangular grid".to_string());
+    }
+    let (width, height) = (grid[0].len(), grid.len());
+    let length = word.chars().count();
+    let directions: Vec<Direction> = directions
         .iter()
-        .map(|f| f(text, x, y))
-        .filter(|x| *x)
-        .count() as u64
+        .filter(|direction| length <= cycle_length(width, height, **direction))
+        .copied()
+        .collect();
+    Ok(search_with(grid, word, &directions, wrap))
 }
 
-fn check_lr(text: &Vec<Vec<char>>, x: usize, y: usize) -> bool {
-    if x + 3 >= text[y].len() {
-        return false;
+// Aho-Corasick automaton over a word list. Every word is inserted both as written
+// and reversed, so reading a line once forwards also finds the words running
+// backwards along it.
+struct Dictionary {
+    goto: Vec<HashMap<char, usize>>,
+    fail: Vec<usize>,
+    // (word index, length, reversed) for every entry ending at the node.
+    outputs: Vec<Vec<(usize, usize, bool)>>,
+}
+
+impl Dictionary {
+    fn new(words: &[&str]) -> Dictionary {
+        let mut dictionary = Dictionary {
+            goto: vec![HashMap::new()],
+            fail: vec![0],
+            outputs: vec![Vec::new()],
+        };
+        for (index, word) in words.iter().enumerate() {
+            let chars: Vec<char> = word.chars().collect();
+            if chars.is_empty() {
+                continue;
+            }
+            dictionary.insert(chars.iter().copied(), (index, chars.len(), false));
+            dictionary.insert(chars.iter().rev().copied(), (index, chars.len(), true));
+        }
+        dictionary.link();
+        dictionary
+    }
+
+    fn insert(&mut self, word: impl Iterator<Item = char>, output: (usize, usize, bool)) {
+        let mut node = 0;
+        for c in word {
+            node = match self.goto[node].get(&c) {
+                Some(next) => *next,
+                None => {
+                    self.goto.push(HashMap::new());
+                    self.fail.push(0);
+                    self.outputs.push(Vec::new());
+                    let next = self.goto.len() - 1;
+                    self.goto[node].insert(c, next);
+                    next
+                }
+            };
+        }
+        self.outputs[node].push(output);
+    }
+
+    // Breadth-first so every failure link points at an already finished node.
+    fn link(&mut self) {
+        let mut queue: VecDeque<usize> = self.goto[0].values().copied().collect();
+        while let Some(node) = queue.pop_front() {
+            let children: Vec<(char, usize)> =
+                self.goto[node].iter().map(|(c, n)| (*c, *n)).collect();
+            for (c, child) in children {
+                let mut fallback = self.fail[node];
+                let target = loop {
+                    if let Some(next) = self.goto[fallback].get(&c) {
+                        break *next;
+                    }
+                    if fallback == 0 {
+                        break 0;
+                    }
+                    fallback = self.fail[fallback];
+                };
+                self.fail[child] = target;
+                let inherited = self.outputs[target].clone();
+                self.outputs[child].extend(inherited);
+                queue.push_back(child);
+            }
+        }
+    }
+
+    fn next(&self, mut node: usize, c: char) -> usize {
+        loop {
+            if let Some(next) = self.goto[node].get(&c) {
+                return *next;
+            }
+            if node == 0 {
+                return 0;
+            }
+            node = self.fail[node];
+        }
     }
-    text[y][x] == 'X' && text[y][x + 1] == 'M' && text[y][x + 2] == 'A' && text[y][x + 3] == 'S'
 }
 
-fn check_rl(text: &Vec<Vec<char>>, x: usize, y: usize) -> bool {
-    if x < 3 {
-        return false;
+#[derive(Hash, Eq, PartialEq, Debug, Copy, Clone)]
+struct DictionaryMatch {
+    word: usize,
+    found: Match,
+}
+
+// Starts of every maximal straight line through the grid: the cells with no
+// predecessor in each of the `FORWARD` directions.
+fn line_starts(grid: &Grid) -> impl Iterator<Item = (Direction, Position)> + '_ {
+    FORWARD.into_iter().flat_map(move |direction| {
+        grid.iter()
+            .enumerate()
+            .flat_map(|(y, line)| (0..line.len()).map(move |x| Position { x, y }))
+            .filter(move |start| walk(grid, *start, direction.opposite(), 1).is_none())
+            .map(move |start| (direction, start))
+    })
+}
+
+// Each line is walked cell by cell while feeding the automaton, so nothing is
+// allocated per line.
+fn dictionary_search(grid: &Grid, words: &[&str]) -> Vec<DictionaryMatch> {
+    let dictionary = Dictionary::new(words);
+    let mut matches = Vec::new();
+    for (direction, start) in line_starts(grid) {
+        let mut node = 0;
+        let mut cell = Some(start);
+        while let Some(pos) = cell {
+            node = dictionary.next(node, grid[pos.y][pos.x]);
+            for (word, length, reversed) in &dictionary.outputs[node] {
+                let found = if *reversed {
+                    Match {
+                        start: pos,
+                        direction: direction.opposite(),
+                    }
+                } else {
+                    Match {
+                        start: walk(grid, pos, direction.opposite(), length - 1).unwrap(),
+                        direction,
+                    }
+                };
+                matches.push(DictionaryMatch { word: *word, found });
+            }
+            cell = walk(grid, pos, direction, 1);
+        }
     }
-    text[y][x] == 'X' && text[y][x - 1] == 'M' && text[y][x - 2] == 'A' && text[y][x - 3] == 'S'
+    matches
 }
 
-fn check_d(text: &Vec<Vec<char>>, x: usize, y: usize) -> bool {
-    if y + 3 >= text.len() {
-        return false;
+// The cells covered by each XMAS, in reading order.
+fn matches_01(text: &Grid) -> Vec<Vec<Position>> {
+    word_search(text, "XMAS", &EIGHT_WAY)
+        .iter()
+        .map(|m| {
+            (0..4)
+                .filter_map(|steps| walk(text, m.start, m.direction, steps))
+                .collect()
+        })
+        .collect()
+}
+
+fn challenge_01(text: &Grid) -> u64 {
+    matches_01(text).len() as u64
+}
+
+// A small rectangular grid where `None` cells match any character.
+#[derive(Hash, Eq, PartialEq, Debug, Clone)]
+struct Pattern {
+    cells: Vec<Vec<Option<char>>>,
+}
+
+impl std::str::FromStr for Pattern {
+    type Err = String;
+
+    // Rows are separated by `/` and `.` is the wildcard, e.g. `M.S/.A./M.S`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cells: Vec<Vec<Option<char>>> = s
+            .split('/')
+            .map(|row| {
+                row.trim()
+                    .chars()
+                    .map(|c| if c == '.' { None } else { Some(c) })
+                    .collect()
+            })
+            .collect();
+        if cells.iter().any(|row| row.is_empty()) {
+            return Err(format!("empty row in pattern: {}", s));
+        }
+        if cells.iter().any(|row| row.len() != cells[0].len()) {
+            return Err(format!("pattern rows differ in length: {}", s));
+        }
+        Ok(Pattern { cells })
     }
-    text[y][x] == 'X' && text[y + 1][x] == 'M' && text[y + 2][x] == 'A' && text[y + 3][x] == 'S'
 }
 
-fn check_dr(text: &Vec<Vec<char>>, x: usize, y: usize) -> bool {
-    if x + 3 >= text[y].len() || y + 3 >= text.len() {
-        return false;
+impl Pattern {
+    fn width(&self) -> usize {
+        self.cells[0].len()
+    }
+
+    fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    // Quarter turn clockwise.
+    fn rotate(&self) -> Pattern {
+        let cells = (0..self.width())
+            .map(|x| (0..self.height()).rev().map(|y| self.cells[y][x]).collect())
+            .collect();
+        Pattern { cells }
+    }
+
+    // Mirror image across the vertical axis.
+    fn reflect(&self) -> Pattern {
+        let cells = self
+            .cells
+            .iter()
+            .map(|row| row.iter().rev().copied().collect())
+            .collect();
+        Pattern { cells }
+    }
+
+    // The pattern itself followed by the requested symmetries, without duplicates,
+    // so a symmetric shape is not counted more than once at the same place.
+    fn variants(&self, rotations: bool, reflections: bool) -> Vec<Pattern> {
+        let mut bases = vec![self.clone()];
+        if reflections {
+            bases.push(self.reflect());
+        }
+        let mut variants: Vec<Pattern> = Vec::new();
+        for base in bases {
+            let turns = if rotations { 4 } else { 1 };
+            let mut current = base;
+            for _ in 0..turns {
+                let next = current.rotate();
+                if !variants.contains(&current) {
+                    variants.push(current);
+                }
+                current = next;
+            }
+        }
+        variants
+    }
+
+    fn cells_at(&self, start: Position) -> Vec<Position> {
+        let mut cells = Vec::new();
+        for (dy, row) in self.cells.iter().enumerate() {
+            for (dx, cell) in row.iter().enumerate() {
+                if cell.is_some() {
+                    cells.push(Position {
+                        x: start.x + dx,
+                        y: start.y + dy,
+                    });
+                }
+            }
+        }
+        cells
+    }
+
+    fn matches_at(&self, grid: &Grid, start: Position) -> bool {
+        self.cells.iter().enumerate().all(|(dy, row)| {
+            grid.get(start.y + dy).is_some_and(|line| {
+                row.iter().enumerate().all(|(dx, cell)| {
+                    line.get(start.x + dx)
+                        .is_some_and(|c| cell.is_none_or(|p| p == *c))
+                })
+            })
+        })
     }
-    text[y][x] == 'X'
-        && text[y + 1][x + 1] == 'M'
-        && text[y + 2][x + 2] == 'A'
-        && text[y + 3][x + 3] == 'S'
 }
 
-fn check_dl(text: &Vec<Vec<char>>, x: usize, y: usize) -> bool {
-    if x < 3 || y + 3 >= text.len() {
-        return false;
+#[derive(Hash, Eq, PartialEq, Debug, Copy, Clone)]
+struct PatternMatch {
+    start: Position,
+    variant: usize,
+}
+
+fn pattern_search(grid: &Grid, variants: &[Pattern]) -> Vec<PatternMatch> {
+    let mut matches = Vec::new();
+    for (y, line) in grid.iter().enumerate() {
+        for x in 0..line.len() {
+            let start = Position { x, y };
+            for (variant, pattern) in variants.iter().enumerate() {
+                if pattern.matches_at(grid, start) {
+                    matches.push(PatternMatch { start, variant });
+                }
+            }
+        }
     }
-    text[y][x] == 'X'
-        && text[y + 1][x - 1] == 'M'
-        && text[y + 2][x - 2] == 'A'
-        && text[y + 3][x - 3] == 'S'
+    matches
+}
+
+// The non-wildcard cells covered by each X-MAS.
+fn matches_02(text: &Grid) -> Vec<Vec<Position>> {
+    let x_mas: Pattern = "M.S/.A./M.S".parse().unwrap();
+    let variants = x_mas.variants(true, true);
+    pattern_search(text, &variants)
+        .iter()
+        .map(|m| variants[m.variant].cells_at(m.start))
+        .collect()
+}
+
+fn challenge_02(text: &Grid) -> u64 {
+    matches_02(text).len() as u64
 }
 
-fn check_u(text: &Vec<Vec<char>>, x: usize, y: usize) -> bool {
-    if y < 3 {
-        return false;
+// The grid with every cell outside the given matches replaced by `.`, as in the
+// puzzle's illustrations.
+fn render(grid: &Grid, matches: &[Vec<Position>]) -> String {
+    let mut shown: Vec<Vec<bool>> = grid.iter().map(|line| vec![false; line.len()]).collect();
+    for pos in matches.iter().flatten() {
+        shown[pos.y][pos.x] = true;
     }
-    text[y][x] == 'X' && text[y - 1][x] == 'M' && text[y - 2][x] == 'A' && text[y - 3][x] == 'S'
+    grid.iter()
+        .zip(shown)
+        .map(|(line, shown)| {
+            line.iter()
+                .zip(shown)
+                .map(|(c, shown)| if shown { *c } else { '.' })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
 }
 
-fn check_ur(text: &Vec<Vec<char>>, x: usize, y: usize) -> bool {
-    if x + 3 >= text[y].len() || y < 3 {
-        return false;
+// Xorshift generator so puzzles can be reproduced from their seed.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
     }
-    text[y][x] == 'X'
-        && text[y - 1][x + 1] == 'M'
-        && text[y - 2][x + 2] == 'A'
-        && text[y - 3][x + 3] == 'S'
 }
 
-fn check_ul(text: &Vec<Vec<char>>, x: usize, y: usize) -> bool {
-    if x < 3 || y < 3 {
-        return false;
+pub struct PuzzleSpec {
+    pub width: usize,
+    pub height: usize,
+    // Each word with the number of copies to plant.
+    pub words: Vec<(String, usize)>,
+    pub directions: String,
+    pub x_mas: usize,
+    // Planted words may share cells holding the same letter.
+    pub overlap: bool,
+    // Letters the rest of the grid is filled with.
+    pub alphabet: String,
+    pub seed: u64,
+}
+
+#[derive(Debug)]
+struct Puzzle {
+    grid: Grid,
+    // Occurrences of each spec word in all eight directions, planted or not.
+    counts: Vec<usize>,
+    x_mas: usize,
+}
+
+const HOLE: char = '\0';
+const PLACEMENT_ATTEMPTS: usize = 1000;
+
+// The generator counts occurrences with its own checks rather than the
+// solvers', so its counts can serve as the expected answers.
+fn word_at(grid: &Grid, word: &[char], (x, y): (isize, isize), (dx, dy): (isize, isize)) -> bool {
+    word.iter().enumerate().all(|(i, c)| {
+        let (x, y) = (x + dx * i as isize, y + dy * i as isize);
+        x >= 0 && y >= 0 && grid.get(y as usize).and_then(|row| row.get(x as usize)) == Some(c)
+    })
+}
+
+// Both diagonals through the `A` at `(x, y)` read MAS in either direction.
+fn x_mas_at(grid: &Grid, (x, y): (isize, isize)) -> bool {
+    let cell = |dx: isize, dy: isize| {
+        let (x, y) = (x + dx, y + dy);
+        if x < 0 || y < 0 {
+            return None;
+        }
+        grid.get(y as usize)
+            .and_then(|row| row.get(x as usize))
+            .copied()
+    };
+    let diagonal = |a, b| matches!((a, b), (Some('M'), Some('S')) | (Some('S'), Some('M')));
+    cell(0, 0) == Some('A')
+        && diagonal(cell(-1, -1), cell(1, 1))
+        && diagonal(cell(1, -1), cell(-1, 1))
+}
+
+// Occurrences of every word starting in the window spanned by the two corners
+// (inclusive), followed by the X-MAS crosses centred in it.
+fn tally_window(grid: &Grid, words: &[Vec<char>], (from, to): (Position, Position)) -> Vec<usize> {
+    let mut counts = vec![0; words.len() + 1];
+    for y in from.y..=to.y {
+        for x in from.x..=to.x {
+            let start = (x as isize, y as isize);
+            for (count, word) in counts.iter_mut().zip(words) {
+                *count += EIGHT_WAY
+                    .iter()
+                    .filter(|direction| word_at(grid, word, start, direction.delta()))
+                    .count();
+            }
+            counts[words.len()] += x_mas_at(grid, start) as usize;
+        }
     }
-    text[y][x] == 'X'
-        && text[y - 1][x - 1] == 'M'
-        && text[y - 2][x - 2] == 'A'
-        && text[y - 3][x - 3] == 'S'
+    counts
 }
 
-fn challenge_02(text: &Vec<Vec<char>>) -> u64 {
-    text.iter()
-        .enumerate()
-        .map(|(y, line)| {
-            line.iter()
-                .enumerate()
-                .map(|(x, _)| find_mas(text, x, y))
-                .sum::<u64>()
+// Occurrences that use the cell at `pos`, in the same layout as `tally_window`.
+fn tally_through(grid: &Grid, words: &[Vec<char>], pos: Position) -> Vec<usize> {
+    let (x, y) = (pos.x as isize, pos.y as isize);
+    let mut counts: Vec<usize> = words
+        .iter()
+        .map(|word| {
+            EIGHT_WAY
+                .iter()
+                .flat_map(|direction| {
+                    let (dx, dy) = direction.delta();
+                    (0..word.len() as isize)
+                        .filter(move |i| word_at(grid, word, (x - dx * i, y - dy * i), (dx, dy)))
+                })
+                .count()
         })
-        .sum()
+        .collect();
+    // A cross uses its centre and the four diagonal neighbours.
+    counts.push(
+        [(0, 0), (-1, -1), (1, -1), (-1, 1), (1, 1)]
+            .iter()
+            .filter(|(dx, dy)| x_mas_at(grid, (x + dx, y + dy)))
+            .count(),
+    );
+    counts
 }
 
-fn find_mas(text: &Vec<Vec<char>>, x: usize, y: usize) -> u64 {
-    let counters = vec![
-        mas_diagonal_lr,
-        mas_diagonal_rl,
-    ];
-    if text[y][x] == 'A' && counters.iter().map(|f| f(text, x, y)).sum::<u64>() == 2 {
-        return 1;
+// Writes `cells` into the grid if they only land on holes (or, with `overlap`,
+// on identical letters) and the grid gains exactly the occurrences the
+// placement holds on its own, so every planted copy is a distinct occurrence.
+// Only occurrences that can reach a written cell are recounted. Returns the
+// occurrences gained, in the layout of `tally_window`.
+fn plant(
+    grid: &mut Grid,
+    cells: &[(Position, char)],
+    overlap: bool,
+    words: &[Vec<char>],
+) -> Option<Vec<usize>> {
+    let fits = cells.iter().all(|(pos, c)| {
+        let current = grid[pos.y][pos.x];
+        current == HOLE || (overlap && current == *c)
+    });
+    if !fits {
+        return None;
+    }
+    let reach = words.iter().map(Vec::len).max().unwrap_or(0).max(3) - 1;
+    let low = Position {
+        x: cells.iter().map(|(pos, _)| pos.x).min().unwrap(),
+        y: cells.iter().map(|(pos, _)| pos.y).min().unwrap(),
+    };
+    let high = Position {
+        x: cells.iter().map(|(pos, _)| pos.x).max().unwrap(),
+        y: cells.iter().map(|(pos, _)| pos.y).max().unwrap(),
+    };
+    let window = (
+        Position {
+            x: low.x.saturating_sub(reach),
+            y: low.y.saturating_sub(reach),
+        },
+        Position {
+            x: (high.x + reach).min(grid[0].len() - 1),
+            y: (high.y + reach).min(grid.len() - 1),
+        },
+    );
+    let mut solo = vec![vec![HOLE; high.x - low.x + 1]; high.y - low.y + 1];
+    for (pos, c) in cells {
+        solo[pos.y - low.y][pos.x - low.x] = *c;
+    }
+    let solo_window = (
+        Position { x: 0, y: 0 },
+        Position {
+            x: high.x - low.x,
+            y: high.y - low.y,
+        },
+    );
+    let gained = tally_window(&solo, words, solo_window);
+    let before = tally_window(grid, words, window);
+    let previous: Vec<char> = cells.iter().map(|(pos, _)| grid[pos.y][pos.x]).collect();
+    for (pos, c) in cells {
+        grid[pos.y][pos.x] = *c;
+    }
+    let after = tally_window(grid, words, window);
+    if (0..after.len()).any(|i| after[i] != before[i] + gained[i]) {
+        for ((pos, _), c) in cells.iter().zip(previous) {
+            grid[pos.y][pos.x] = c;
+        }
+        return None;
     }
-    0
+    Some(gained)
 }
 
-fn mas_diagonal_lr(text: &Vec<Vec<char>>, x: usize, y: usize) -> u64 {
-    if x > 0 && x < text[y].len() - 1 && y > 0 && y < text.len() - 1 {
-        if text[y - 1][x - 1] == 'M' && text[y + 1][x + 1] == 'S' {
-            return 1;
-        } else if text[y - 1][x - 1] == 'S' && text[y + 1][x + 1] == 'M' {
-            return 1;
+// Plants the requested words and crosses, then fills the rest from the
+// alphabet. Each hole gets a letter that completes no occurrence where the
+// alphabet allows it; any it cannot avoid are counted as decoys.
+fn generate(spec: &PuzzleSpec) -> Result<Puzzle, String> {
+    if spec.width == 0 || spec.height == 0 {
+        return Err("puzzle dimensions must be positive".to_string());
+    }
+    let directions = direction_set(&spec.directions)
+        .ok_or(format!("unknown direction set: {}", spec.directions))?;
+    let filler: Vec<char> = spec.alphabet.chars().collect();
+    if filler.is_empty() {
+        return Err("the fill alphabet is empty".to_string());
+    }
+    let words: Vec<Vec<char>> = spec
+        .words
+        .iter()
+        .map(|(word, _)| word.chars().collect())
+        .collect();
+    let x_mas: Pattern = "M.S/.A./M.S".parse().unwrap();
+    let variants = x_mas.variants(true, true);
+    let mut rng = Rng::new(spec.seed);
+    let mut grid = vec![vec![HOLE; spec.width]; spec.height];
+    let mut counts = vec![0; words.len() + 1];
+    let mut add = |gained: Vec<usize>| {
+        for (count, gained) in counts.iter_mut().zip(gained) {
+            *count += gained;
+        }
+    };
+    for (word, (name, copies)) in words.iter().zip(&spec.words) {
+        for _ in 0..*copies {
+            let gained = (0..PLACEMENT_ATTEMPTS).find_map(|_| {
+                let start = Position {
+                    x: rng.below(spec.width),
+                    y: rng.below(spec.height),
+                };
+                let direction = directions[rng.below(directions.len())];
+                let cells: Vec<(Position, char)> = word
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| walk(&grid, start, direction, i).map(|pos| (pos, *c)))
+                    .collect::<Option<_>>()?;
+                if cells.is_empty() {
+                    return None;
+                }
+                plant(&mut grid, &cells, spec.overlap, &words)
+            });
+            add(gained.ok_or(format!("could not place {} in the grid", name))?);
         }
     }
-    0
+    for _ in 0..spec.x_mas {
+        let gained = (spec.width >= 3 && spec.height >= 3)
+            .then(|| {
+                (0..PLACEMENT_ATTEMPTS).find_map(|_| {
+                    let pattern = &variants[rng.below(variants.len())];
+                    let start = Position {
+                        x: rng.below(spec.width - 2),
+                        y: rng.below(spec.height - 2),
+                    };
+                    let cells: Vec<(Position, char)> = pattern
+                        .cells_at(start)
+                        .into_iter()
+                        .map(|pos| {
+                            (
+                                pos,
+                                pattern.cells[pos.y - start.y][pos.x - start.x].unwrap(),
+                            )
+                        })
+                        .collect();
+                    plant(&mut grid, &cells, spec.overlap, &words)
+                })
+            })
+            .flatten();
+        add(gained.ok_or("could not place an X-MAS in the grid".to_string())?);
+    }
+    for y in 0..spec.height {
+        for x in 0..spec.width {
+            if grid[y][x] != HOLE {
+                continue;
+            }
+            let pos = Position { x, y };
+            let first = rng.below(filler.len());
+            let mut decoys = Vec::new();
+            for k in 0..filler.len() {
+                grid[y][x] = filler[(first + k) % filler.len()];
+                decoys = tally_through(&grid, &words, pos);
+                if decoys.iter().all(|count| *count == 0) {
+                    break;
+                }
+            }
+            if decoys.iter().any(|count| *count > 0) {
+                grid[y][x] = filler[first];
+                decoys = tally_through(&grid, &words, pos);
+            }
+            add(decoys);
+        }
+    }
+    let x_mas = counts.pop().unwrap();
+    Ok(Puzzle {
+        grid,
+        counts,
+        x_mas,
+    })
+}
+
+// The generated grid followed by the expected counts.
+pub fn generate_puzzle(spec: &PuzzleSpec) -> Result<String, String> {
+    let puzzle = generate(spec)?;
+    let mut lines: Vec<String> = puzzle
+        .grid
+        .iter()
+        .map(|line| line.iter().collect())
+        .collect();
+    lines.push(String::new());
+    for ((word, _), count) in spec.words.iter().zip(&puzzle.counts) {
+        lines.push(format!("{}: {}", word, count));
+    }
+    lines.push(format!("X-MAS: {}", puzzle.x_mas));
+    Ok(lines.join("\n"))
+}
+
+// Rectangular grid of ASCII bytes stored row-major in one allocation, for inputs
+// far larger than the puzzle's. Rows are scanned in parallel.
+struct ByteGrid {
+    width: usize,
+    height: usize,
+    cells: Vec<u8>,
 }
 
-fn mas_diagonal_rl(text: &Vec<Vec<char>>, x: usize, y: usize) -> u64 {
-    if x > 0 && x < text[y].len() - 1 && y > 0 && y < text.len() - 1 {
-        if text[y - 1][x + 1] == 'M' && text[y + 1][x - 1] == 'S' {
-            return 1;
-        } else if text[y - 1][x + 1] == 'S' && text[y + 1][x - 1] == 'M' {
-            return 1;
+impl ByteGrid {
+    // Strips the line breaks in place, so the grid costs one byte per cell.
+    // Only ASCII is accepted, and blank lines only at the end.
+    fn from_bytes(mut bytes: Vec<u8>) -> Result<ByteGrid, String> {
+        let mut width = None;
+        let mut height = 0;
+        let mut write = 0;
+        let mut line_start = 0;
+        let mut line = 1;
+        let mut blank_line = None;
+        // The end of the input closes the last row like a line break would.
+        for read in 0..=bytes.len() {
+            let b = bytes.get(read).copied().unwrap_or(b'\n');
+            if b == b'\r' {
+                continue;
+            }
+            if !b.is_ascii() {
+                return Err(format!("line {} has a non-ASCII byte", line));
+            }
+            if b != b'\n' {
+                bytes[write] = b;
+                write += 1;
+                continue;
+            }
+            let length = write - line_start;
+            if length == 0 {
+                blank_line.get_or_insert(line);
+            } else if let Some(blank) = blank_line {
+                return Err(format!("blank line {} inside the grid", blank));
+            } else if *width.get_or_insert(length) != length {
+                return Err(format!(
+                    "row {} has {} cells instead of {}",
+                    height,
+                    length,
+                    width.unwrap()
+                ));
+            } else {
+                height += 1;
+                line_start = write;
+            }
+            line += 1;
+        }
+        bytes.truncate(write);
+        bytes.shrink_to_fit();
+        Ok(ByteGrid {
+            width: width.unwrap_or(0),
+            height,
+            cells: bytes,
+        })
+    }
+
+    fn get(&self, x: isize, y: isize) -> Option<u8> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return None;
         }
+        Some(self.cells[y as usize * self.width + x as usize])
+    }
+
+    fn row(&self, y: usize) -> &[u8] {
+        &self.cells[y * self.width..(y + 1) * self.width]
     }
-    0
+}
+
+fn count_word_bytes(grid: &ByteGrid, word: &[u8]) -> u64 {
+    if word.is_empty() {
+        return 0;
+    }
+    (0..grid.height)
+        .into_par_iter()
+        .map(|y| {
+            let mut count = 0;
+            for (x, b) in grid.row(y).iter().enumerate() {
+                if *b != word[0] {
+                    continue;
+                }
+                for direction in EIGHT_WAY {
+                    let (dx, dy) = direction.delta();
+                    let found = word.iter().enumerate().skip(1).all(|(i, c)| {
+                        let i = i as isize;
+                        grid.get(x as isize + dx * i, y as isize + dy * i) == Some(*c)
+                    });
+                    if found {
+                        count += 1;
+                    }
+                }
+            }
+            count
+        })
+        .sum()
+}
+
+fn count_x_mas_bytes(grid: &ByteGrid) -> u64 {
+    if grid.width < 3 || grid.height < 3 {
+        return 0;
+    }
+    let is_mas = |a: u8, b: u8| (a == b'M' && b == b'S') || (a == b'S' && b == b'M');
+    (1..grid.height - 1)
+        .into_par_iter()
+        .map(|y| {
+            let (above, row, below) = (grid.row(y - 1), grid.row(y), grid.row(y + 1));
+            (1..grid.width - 1)
+                .filter(|x| {
+                    row[*x] == b'A'
+                        && is_mas(above[x - 1], below[x + 1])
+                        && is_mas(above[x + 1], below[x - 1])
+                })
+                .count() as u64
+        })
+        .sum()
+}
+
+pub fn large_parts(path: &str) -> io::Result<(u64, u64)> {
+    let grid = ByteGrid::from_bytes(fs::read(path)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok((count_word_bytes(&grid, b"XMAS"), count_x_mas_bytes(&grid)))
 }
 
 fn get_data() -> Vec<Vec<char>> {
@@ -148,6 +934,46 @@ fn get_data() -> Vec<Vec<char>> {
     text
 }
 
+pub fn count_word(word: &str, directions: &str, wrapping: bool) -> Result<usize, String> {
+    let directions =
+        direction_set(directions).ok_or(format!("unknown direction set: {}", directions))?;
+    if wrapping {
+        return Ok(wrapping_search(&get_data(), word, directions)?.len());
+    }
+    Ok(word_search(&get_data(), word, directions).len())
+}
+
+pub fn read_word_list(path: &str) -> io::Result<Vec<String>> {
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+pub fn count_words(words: &[&str]) -> Vec<usize> {
+    let mut counts = vec![0; words.len()];
+    for found in dictionary_search(&get_data(), words) {
+        counts[found.word] += 1;
+    }
+    counts
+}
+
+pub fn count_pattern(pattern: &str, rotations: bool, reflections: bool) -> Result<usize, String> {
+    let pattern: Pattern = pattern.parse()?;
+    Ok(pattern_search(&get_data(), &pattern.variants(rotations, reflections)).len())
+}
+
+pub fn render_part(part: usize) -> Option<String> {
+    let text = get_data();
+    match part {
+        1 => Some(render(&text, &matches_01(&text))),
+        2 => Some(render(&text, &matches_02(&text))),
+        _ => None,
+    }
+}
+
 pub fn part_1() -> u64 {
     let text = get_data();
     challenge_01(&text)
@@ -198,4 +1024,428 @@ mod test {
         // Assert
         assert_eq!(count, 9);
     }
+
+    fn get_example() -> Grid {
+        [
+            "MMMSXXMASM",
+            "MSAMXMSMSA",
+            "AMXSXMAAMM",
+            "MSAMASMSMX",
+            "XMASAMXAMM",
+            "XXAMMXXAMA",
+            "SMSMSASXSS",
+            "SAXAMASAAA",
+            "MAMMMXMMMM",
+            "MXMXAXMASX",
+        ]
+        .iter()
+        .map(|line| line.chars().collect())
+        .collect()
+    }
+
+    #[test]
+    fn check_example() {
+        // Arrange
+        let input = get_example();
+        // Act and Assert
+        assert_eq!(challenge_01(&input), 18);
+        assert_eq!(challenge_02(&input), 9);
+    }
+
+    #[test]
+    fn check_word_search() {
+        // Arrange
+        let input = vec![
+            vec!['.', '.', 'X', '.', '.', '.'],
+            vec!['.', 'S', 'A', 'M', 'X', '.'],
+            vec!['.', 'A', '.', '.', 'A', '.'],
+            vec!['X', 'M', 'A', 'S', '.', 'S'],
+            vec!['.', 'X', '.', '.', '.', '.'],
+        ];
+        // Act
+        let actual = word_search(&input, "XMAS", &EIGHT_WAY);
+        // Assert
+        assert_eq!(
+            actual,
+            vec![
+                Match {
+                    start: Position { x: 2, y: 0 },
+                    direction: Direction::DownRight
+                },
+                Match {
+                    start: Position { x: 4, y: 1 },
+                    direction: Direction::Left
+                },
+                Match {
+                    start: Position { x: 0, y: 3 },
+                    direction: Direction::Right
+                },
+                Match {
+                    start: Position { x: 1, y: 4 },
+                    direction: Direction::Up
+                },
+            ]
+        );
+        assert_eq!(word_search(&input, "XMAS", &FOUR_WAY).len(), 3);
+        assert_eq!(word_search(&input, "XMAS", &FORWARD).len(), 2);
+        assert_eq!(word_search(&input, "SAMX", &FORWARD).len(), 2);
+        assert!(word_search(&input, "", &EIGHT_WAY).is_empty());
+    }
+
+    #[test]
+    fn check_pattern_variants() {
+        // Arrange
+        let x_mas: Pattern = "M.S/.A./M.S".parse().unwrap();
+        let corner: Pattern = "AB/C.".parse().unwrap();
+        // Act and Assert
+        assert_eq!(x_mas.rotate(), "M.M/.A./S.S".parse().unwrap());
+        assert_eq!(x_mas.reflect(), "S.M/.A./S.M".parse().unwrap());
+        assert_eq!(x_mas.variants(false, false).len(), 1);
+        assert_eq!(x_mas.variants(true, false).len(), 4);
+        assert_eq!(x_mas.variants(true, true).len(), 4);
+        assert_eq!(corner.variants(true, true).len(), 8);
+        assert_eq!(
+            "ABC".parse::<Pattern>().unwrap().variants(true, true).len(),
+            4
+        );
+        assert!("AB/C".parse::<Pattern>().is_err());
+        assert!("AB//CD".parse::<Pattern>().is_err());
+    }
+
+    #[test]
+    fn check_pattern_search() {
+        // Arrange
+        let input = vec![
+            vec!['M', 'X', 'S', 'S'],
+            vec!['.', 'A', '.', 'A'],
+            vec!['M', '.', 'S', 'M'],
+        ];
+        let x_mas: Pattern = "M.S/.A./M.S".parse().unwrap();
+        // Act
+        let actual = pattern_search(&input, &x_mas.variants(true, true));
+        // Assert
+        assert_eq!(
+            actual,
+            vec![PatternMatch {
+                start: Position { x: 0, y: 0 },
+                variant: 0
+            }]
+        );
+        assert!(pattern_search(&input, &x_mas.variants(false, false))
+            .iter()
+            .all(|m| m.variant == 0));
+        let rotated = "S.S/.A./M.M".parse::<Pattern>().unwrap();
+        assert_eq!(pattern_search(&input, &[rotated]).len(), 0);
+    }
+
+    #[test]
+    fn check_dictionary_search() {
+        // Arrange
+        let input = get_example();
+        let words = ["XMAS", "MAS", "SAMX", "A", "AMA", "", "XMASXMAS"];
+        // Act
+        let actual = dictionary_search(&input, &words);
+        // Assert
+        for (index, word) in words.iter().enumerate() {
+            let mut expected = word_search(&input, word, &EIGHT_WAY);
+            let mut found: Vec<Match> = actual
+                .iter()
+                .filter(|m| m.word == index)
+                .map(|m| m.found)
+                .collect();
+            expected.sort_by_key(|m| (m.start.y, m.start.x, m.direction as usize));
+            found.sort_by_key(|m| (m.start.y, m.start.x, m.direction as usize));
+            assert_eq!(found, expected, "{}", word);
+        }
+    }
+
+    #[test]
+    fn check_dictionary_search_ragged() {
+        // Arrange
+        let input: Grid = ["XMAS", "MM", "AXA", "SAMXS", "", "XMASAMX"]
+            .iter()
+            .map(|line| line.chars().collect())
+            .collect();
+        let words = ["XMAS", "MAS", "XS"];
+        // Act
+        let actual = dictionary_search(&input, &words);
+        // Assert
+        for (index, word) in words.iter().enumerate() {
+            let found = actual.iter().filter(|m| m.word == index).count();
+            assert_eq!(
+                found,
+                word_search(&input, word, &EIGHT_WAY).len(),
+                "{}",
+                word
+            );
+        }
+    }
+
+    #[test]
+    fn check_dictionary_overlaps() {
+        // Arrange
+        let input = vec![vec!['A', 'B', 'A', 'B', 'A']];
+        // Act
+        let actual = dictionary_search(&input, &["ABA", "BAB", "B"]);
+        // Assert
+        let count = |word| actual.iter().filter(|m| m.word == word).count();
+        assert_eq!(count(0), 4);
+        assert_eq!(count(1), 2);
+        assert_eq!(count(2), 16);
+    }
+
+    #[test]
+    fn check_render() {
+        // Arrange
+        let input = get_example();
+        // Act
+        let part_1 = render(&input, &matches_01(&input));
+        let part_2 = render(&input, &matches_02(&input));
+        // Assert
+        assert_eq!(
+            part_1,
+            [
+                "....XXMAS.",
+                ".SAMXMS...",
+                "...S..A...",
+                "..A.A.MS.X",
+                "XMASAMX.MM",
+                "X.....XA.A",
+                "S.S.S.S.SS",
+                ".A.A.A.A.A",
+                "..M.M.M.MM",
+                ".X.X.XMASX",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            part_2,
+            [
+                ".M.S......",
+                "..A..MSMS.",
+                ".M.S.MAA..",
+                "..A.ASMSM.",
+                ".M.S.M....",
+                "..........",
+                "S.S.S.S.S.",
+                ".A.A.A.A..",
+                "M.M.M.M.M.",
+                "..........",
+            ]
+            .join("\n")
+        );
+        assert_eq!(matches_02(&input)[0].len(), 5);
+    }
+
+    #[test]
+    fn check_wrapping_search() {
+        // Arrange
+        let input = vec![
+            vec!['A', 'S', '.', 'X', 'M'],
+            vec!['.', '.', '.', '.', '.'],
+            vec!['.', '.', '.', '.', 'S'],
+            vec!['M', '.', '.', '.', '.'],
+        ];
+        // Act
+        let actual = wrapping_search(&input, "XMAS", &EIGHT_WAY).unwrap();
+        // Assert
+        assert_eq!(
+            actual,
+            vec![Match {
+                start: Position { x: 3, y: 0 },
+                direction: Direction::Right
+            }]
+        );
+        assert!(word_search(&input, "XMAS", &EIGHT_WAY).is_empty());
+        assert_eq!(wrapping_search(&input, "XMAS", &FOUR_WAY).unwrap().len(), 1);
+        assert_eq!(
+            wrapping_search(&input, "SAM", &[Direction::Up])
+                .unwrap()
+                .len(),
+            0
+        );
+        assert_eq!(
+            wrapping_search(&input, "SM", &[Direction::UpLeft])
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn check_wrapping_limit() {
+        // Arrange
+        let input = vec![vec!['A', 'B', 'C', 'D'], vec!['E', 'F', 'G', 'H']];
+        // Act and Assert
+        assert_eq!(cycle_length(4, 2, Direction::Right), 4);
+        assert_eq!(cycle_length(4, 2, Direction::Up), 2);
+        assert_eq!(cycle_length(4, 6, Direction::DownLeft), 12);
+        assert_eq!(
+            wrapping_search(&input, "CDAB", &[Direction::Right])
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(wrapping_search(&input, "CDABC", &[Direction::Right])
+            .unwrap()
+            .is_empty());
+        // Too long to wrap vertically, but fine along the rows.
+        assert!(wrapping_search(&input, "AEA", &FOUR_WAY)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            wrapping_search(&input, "DAB", &FOUR_WAY).unwrap(),
+            vec![Match {
+                start: Position { x: 3, y: 0 },
+                direction: Direction::Right
+            }]
+        );
+        assert!(wrapping_search(&vec![vec!['A'], vec!['B', 'C']], "A", &EIGHT_WAY).is_err());
+    }
+
+    fn get_spec(seed: u64) -> PuzzleSpec {
+        PuzzleSpec {
+            width: 20,
+            height: 15,
+            words: vec![("XMAS".to_string(), 12), ("MAS".to_string(), 3)],
+            directions: "eight".to_string(),
+            x_mas: 6,
+            overlap: true,
+            alphabet: "XMAS".to_string(),
+            seed,
+        }
+    }
+
+    #[test]
+    fn check_generated_puzzles() {
+        for seed in 0..10 {
+            // Arrange
+            let spec = get_spec(seed);
+            // Act
+            let puzzle = generate(&spec).unwrap();
+            // Assert
+            assert!(puzzle.counts[0] >= 12);
+            assert!(puzzle.counts[1] >= 3 + 2 * 6);
+            assert_eq!(puzzle.x_mas, 6);
+            assert_eq!(challenge_01(&puzzle.grid), puzzle.counts[0] as u64);
+            assert_eq!(challenge_02(&puzzle.grid), puzzle.x_mas as u64);
+            let found = dictionary_search(&puzzle.grid, &["XMAS", "MAS"]);
+            assert_eq!(
+                found.iter().filter(|m| m.word == 1).count(),
+                puzzle.counts[1]
+            );
+        }
+    }
+
+    #[test]
+    fn check_plant_bookkeeping() {
+        // Arrange
+        let words: Vec<Vec<char>> = vec!["XMAS".chars().collect(), "MAS".chars().collect()];
+        let mut grid = vec![vec![HOLE; 5]; 5];
+        let cells = |start: (usize, usize), (dx, dy): (isize, isize), word: &str| {
+            word.chars()
+                .enumerate()
+                .map(|(i, c)| {
+                    let x = start.0.checked_add_signed(dx * i as isize).unwrap();
+                    let y = start.1.checked_add_signed(dy * i as isize).unwrap();
+                    (Position { x, y }, c)
+                })
+                .collect::<Vec<(Position, char)>>()
+        };
+        // Act
+        let xmas = plant(&mut grid, &cells((0, 0), (1, 0), "XMAS"), true, &words);
+        let shared = plant(&mut grid, &cells((1, 0), (0, 1), "MAS"), true, &words);
+        let clash = plant(&mut grid, &cells((1, 1), (1, 0), "MAS"), true, &words);
+        let diagonal = plant(&mut grid, &cells((2, 2), (1, 1), "MAS"), true, &words);
+        let cross = plant(&mut grid, &cells((4, 2), (-1, 1), "MAS"), true, &words);
+        let separate = plant(&mut grid, &cells((4, 2), (-1, 1), "MAS"), false, &words);
+        // Assert
+        assert_eq!(xmas, Some(vec![1, 1, 0]));
+        assert_eq!(shared, Some(vec![0, 1, 0]));
+        assert_eq!(clash, None);
+        assert_eq!(diagonal, Some(vec![0, 1, 0]));
+        assert_eq!(cross, None);
+        assert_eq!(separate, None);
+        assert_eq!(grid[2][4], HOLE);
+        assert_eq!(
+            tally_window(
+                &grid,
+                &words,
+                (Position { x: 0, y: 0 }, Position { x: 4, y: 4 })
+            ),
+            vec![1, 3, 0]
+        );
+    }
+
+    #[test]
+    fn check_generator_is_seeded() {
+        // Arrange
+        let spec = get_spec(7);
+        // Act
+        let first = generate_puzzle(&spec).unwrap();
+        let second = generate_puzzle(&spec).unwrap();
+        // Assert
+        assert_eq!(first, second);
+        assert_ne!(first, generate_puzzle(&get_spec(8)).unwrap());
+        assert!(first.contains("\n\nXMAS: "));
+        assert!(first.contains("\nX-MAS: "));
+    }
+
+    #[test]
+    fn check_generator_errors() {
+        // Arrange
+        let mut crowded = get_spec(1);
+        crowded.width = 3;
+        crowded.height = 3;
+        let mut no_alphabet = get_spec(1);
+        no_alphabet.alphabet = String::new();
+        // Act and Assert
+        assert!(generate(&crowded).is_err());
+        assert!(generate(&no_alphabet).is_err());
+    }
+
+    fn to_bytes(grid: &Grid) -> Vec<u8> {
+        grid.iter()
+            .map(|line| line.iter().collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\r\n")
+            .into_bytes()
+    }
+
+    #[test]
+    fn check_byte_grid() {
+        // Arrange
+        let input = get_example();
+        // Act
+        let grid = ByteGrid::from_bytes(to_bytes(&input)).unwrap();
+        // Assert
+        assert_eq!((grid.width, grid.height), (10, 10));
+        assert_eq!(grid.cells.len(), 100);
+        assert_eq!(grid.row(1), b"MSAMXMSMSA");
+        assert_eq!(count_word_bytes(&grid, b"XMAS"), 18);
+        assert_eq!(count_x_mas_bytes(&grid), 9);
+        assert!(ByteGrid::from_bytes(b"ABC\nAB\n".to_vec()).is_err());
+        assert!(ByteGrid::from_bytes(b"AB\n\nCD\n".to_vec()).is_err());
+        assert!(ByteGrid::from_bytes("XMAS\nSAMX\n\u{e9}xx\n".as_bytes().to_vec()).is_err());
+        assert_eq!(
+            ByteGrid::from_bytes(b"AB\r\nCD\r\n\r\n\n".to_vec())
+                .unwrap()
+                .height,
+            2
+        );
+    }
+
+    #[test]
+    fn check_byte_grid_matches_char_grid() {
+        for seed in 0..5 {
+            // Arrange
+            let puzzle = generate(&get_spec(seed)).unwrap();
+            // Act
+            let grid = ByteGrid::from_bytes(to_bytes(&puzzle.grid)).unwrap();
+            // Assert
+            assert_eq!(count_word_bytes(&grid, b"XMAS"), challenge_01(&puzzle.grid));
+            assert_eq!(count_word_bytes(&grid, b"MAS"), puzzle.counts[1] as u64);
+            assert_eq!(count_x_mas_bytes(&grid), challenge_02(&puzzle.grid));
+        }
+    }
 }
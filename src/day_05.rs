@@ -1,5 +1,5 @@
-use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
 use std::fs;
 
 fn challenge_01(ordering: &HashMap<u32, HashSet<u32>>, updates: &Vec<Vec<u32>>) -> u32 {
@@ -29,67 +29,559 @@ fn is_ordered(update: &[u32], ordering: &HashMap<u32, HashSet<u32>>) -> bool {
     }
 }
 
-fn order(update: &Vec<u32>, ordering: &HashMap<u32, HashSet<u32>>) -> Vec<u32> {
-    let mut ordered = update.clone();
-    ordered.sort_by(|a, b| {
-        if ordering.contains_key(a) && ordering[a].contains(b) {
-            Ordering::Less
-        } else if ordering.contains_key(b) && ordering[b].contains(a) {
-            Ordering::Greater
-        } else {
-            a.cmp(b)
-        }
-    });
-    ordered
+// The pages of an update whose restricted rules form a loop; each page must come
+// before the next one and the last before the first.
+#[derive(Debug, PartialEq, Eq)]
+struct Cycle {
+    pages: Vec<u32>,
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pages: Vec<String> = self.pages.iter().map(u32::to_string).collect();
+        write!(
+            f,
+            "ordering rules form a cycle: {} -> {}",
+            pages.join(" -> "),
+            pages[0]
+        )
+    }
 }
 
-fn challenge_02(ordering: &HashMap<u32, HashSet<u32>>, updates: &Vec<Vec<u32>>) -> u32 {
-    updates
+// For each page of the update, the positions of the pages a rule puts after it.
+fn restricted_successors(update: &[u32], ordering: &HashMap<u32, HashSet<u32>>) -> Vec<Vec<usize>> {
+    update
         .iter()
-        .filter(|u| !is_ordered(u, ordering))
-        .map(|f| order(&f, ordering))
-        .map(|v| v[v.len() / 2])
-        .sum()
+        .map(|page| {
+            (0..update.len())
+                .filter(|j| {
+                    ordering
+                        .get(page)
+                        .is_some_and(|after| after.contains(&update[*j]))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Topological sort of the update under the rules that mention two of its pages.
+// Among pages that are free to go next, the one earliest in the update wins, so
+// unrelated pages keep their relative order.
+fn order(update: &[u32], ordering: &HashMap<u32, HashSet<u32>>) -> Result<Vec<u32>, Cycle> {
+    topological_order(update, &restricted_successors(update, ordering))
+}
+
+fn topological_order(update: &[u32], successors: &[Vec<usize>]) -> Result<Vec<u32>, Cycle> {
+    let mut incoming = vec![0; update.len()];
+    for j in successors.iter().flatten() {
+        incoming[*j] += 1;
+    }
+    let mut ready: BTreeSet<usize> = (0..update.len()).filter(|i| incoming[*i] == 0).collect();
+    let mut ordered = Vec::with_capacity(update.len());
+    while let Some(i) = ready.pop_first() {
+        ordered.push(update[i]);
+        for j in &successors[i] {
+            incoming[*j] -= 1;
+            if incoming[*j] == 0 {
+                ready.insert(*j);
+            }
+        }
+    }
+    if ordered.len() < update.len() {
+        return Err(find_cycle(update, successors, &incoming));
+    }
+    Ok(ordered)
+}
+
+// Every page left with incoming rules has a predecessor that is also left, so
+// walking predecessors must eventually revisit a page.
+fn find_cycle(update: &[u32], successors: &[Vec<usize>], incoming: &[usize]) -> Cycle {
+    let predecessor = |j: usize| {
+        (0..update.len())
+            .find(|i| incoming[*i] > 0 && successors[*i].contains(&j))
+            .unwrap()
+    };
+    let mut seen = vec![false; update.len()];
+    let mut current = (0..update.len()).find(|i| incoming[*i] > 0).unwrap();
+    let mut path = Vec::new();
+    while !seen[current] {
+        seen[current] = true;
+        path.push(current);
+        current = predecessor(current);
+    }
+    let start = path.iter().position(|i| *i == current).unwrap();
+    let mut cycle: Vec<usize> = path[start..].iter().rev().copied().collect();
+    // Start from the page that comes first in the update, so the report is stable.
+    let first = (0..cycle.len()).min_by_key(|k| cycle[*k]).unwrap();
+    cycle.rotate_left(first);
+    Cycle {
+        pages: cycle.iter().map(|i| update[*i]).collect(),
+    }
+}
+
+fn challenge_02(
+    ordering: &HashMap<u32, HashSet<u32>>,
+    updates: &Vec<Vec<u32>>,
+) -> Result<u32, Cycle> {
+    let mut sum = 0;
+    for update in updates.iter().filter(|u| !is_ordered(u, ordering)) {
+        let ordered = order(update, ordering)?;
+        sum += ordered[ordered.len() / 2];
+    }
+    Ok(sum)
 }
 
 struct Data {
     ordering: HashMap<u32, HashSet<u32>>,
     updates: Vec<Vec<u32>>,
+    // Every rule as written, duplicates included.
+    rules: Vec<(u32, u32)>,
+    // Lines that are neither a rule nor an update, with their line number.
+    malformed: Vec<String>,
+}
+
+fn get_data() -> Result<Data, String> {
+    read_data("data/day05_01.txt")
+}
+
+// Reads the rules and updates, setting malformed lines aside so the caller can
+// decide whether they are fatal.
+fn read_data(path: &str) -> Result<Data, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    Ok(parse_data(&text))
 }
 
-fn get_data() -> Data {
+fn parse_data(text: &str) -> Data {
     let mut data = Data {
         ordering: HashMap::with_capacity(1200),
         updates: Vec::with_capacity(500),
+        rules: Vec::with_capacity(1200),
+        malformed: Vec::new(),
     };
     let mut first_part = true;
-    for line in fs::read_to_string("data/day05_01.txt").unwrap().lines() {
-        if line == "" {
+    for (idx, line) in text.lines().enumerate() {
+        let page = |n: &str| n.trim().parse::<u32>().ok();
+        if line.trim().is_empty() {
             first_part = false;
         } else if first_part {
-            let slice = line.split("|").collect::<Vec<&str>>();
-            let key: u32 = slice[0].parse().unwrap();
-            let val: u32 = slice[1].parse().unwrap();
-            data.ordering
-                .entry(key)
-                .or_insert(HashSet::new())
-                .insert(val);
+            match line
+                .split_once('|')
+                .map(|(key, val)| (page(key), page(val)))
+            {
+                Some((Some(key), Some(val))) => {
+                    data.rules.push((key, val));
+                    data.ordering
+                        .entry(key)
+                        .or_insert(HashSet::new())
+                        .insert(val);
+                }
+                _ => data.malformed.push(format!(
+                    "line {}: expected a rule like 47|53, got {:?}",
+                    idx + 1,
+                    line
+                )),
+            }
         } else {
-            let slice: Vec<u32> = line.split(",").map(|n| n.parse().unwrap()).collect();
-            data.updates.push(slice);
+            match line.split(",").map(page).collect::<Option<Vec<u32>>>() {
+                Some(update) => data.updates.push(update),
+                None => data.malformed.push(format!(
+                    "line {}: expected comma separated pages, got {:?}",
+                    idx + 1,
+                    line
+                )),
+            }
         }
     }
     data
 }
 
-pub fn part_1() -> u32 {
-    let data = get_data();
-    challenge_01(&data.ordering, &data.updates)
+// The data from `path`, or the bundled input, refusing any malformed line.
+fn load_data(path: Option<&str>) -> Result<Data, String> {
+    let data = match path {
+        Some(path) => read_data(path)?,
+        None => get_data()?,
+    };
+    match data.malformed.first() {
+        Some(line) => Err(line.clone()),
+        None => Ok(data),
+    }
+}
+
+// A rule `before|after` broken by an update that lists `after` first.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct RuleViolation {
+    before: u32,
+    after: u32,
+    before_position: usize,
+    after_position: usize,
+}
+
+impl fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}|{} broken: {} at position {}, {} at position {}",
+            self.before,
+            self.after,
+            self.before,
+            self.before_position,
+            self.after,
+            self.after_position
+        )
+    }
+}
+
+// Every broken rule, ordered by the position of the page that should have come first.
+fn violations(update: &[u32], ordering: &HashMap<u32, HashSet<u32>>) -> Vec<RuleViolation> {
+    let mut found = Vec::new();
+    for (before_position, before) in update.iter().enumerate() {
+        let Some(after_pages) = ordering.get(before) else {
+            continue;
+        };
+        for (after_position, after) in update[..before_position].iter().enumerate() {
+            if after_pages.contains(after) {
+                found.push(RuleViolation {
+                    before: *before,
+                    after: *after,
+                    before_position,
+                    after_position,
+                });
+            }
+        }
+    }
+    found
+}
+
+fn explain(ordering: &HashMap<u32, HashSet<u32>>, updates: &[Vec<u32>]) -> String {
+    let join = |pages: &[u32]| {
+        pages
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<String>>()
+            .join(",")
+    };
+    let mut lines = Vec::new();
+    for (idx, update) in updates.iter().enumerate() {
+        let broken = violations(update, ordering);
+        if broken.is_empty() {
+            continue;
+        }
+        lines.push(format!("update {}: {}", idx + 1, join(update)));
+        lines.extend(broken.iter().map(|v| format!("  {}", v)));
+        match order(update, ordering) {
+            Ok(ordered) => lines.push(format!("  corrected: {}", join(&ordered))),
+            Err(cycle) => lines.push(format!("  cannot correct: {}", cycle)),
+        }
+    }
+    lines.join("\n")
+}
+
+pub fn explain_updates(path: Option<&str>) -> Result<String, String> {
+    let data = load_data(path)?;
+    Ok(explain(&data.ordering, &data.updates))
+}
+
+// A valid order that moves as few pages as possible, and the pages it moved.
+#[derive(Debug, PartialEq, Eq)]
+struct Correction {
+    ordered: Vec<u32>,
+    moved: Vec<u32>,
+}
+
+// Augmenting path search for Kuhn's bipartite matching.
+fn augment(
+    i: usize,
+    edges: &[Vec<usize>],
+    matched: &mut [Option<usize>],
+    seen: &mut [bool],
+) -> bool {
+    for j in &edges[i] {
+        if seen[*j] {
+            continue;
+        }
+        seen[*j] = true;
+        if matched[*j].is_none_or(|other| augment(other, edges, matched, seen)) {
+            matched[*j] = Some(i);
+            return true;
+        }
+    }
+    false
+}
+
+// Pages can stay in place only if no later page must, directly or through other
+// pages of the update, precede an earlier one. Such "inversions" form a partial
+// order on positions, so the largest set of pages that can stay is a maximum
+// antichain, found through a minimum vertex cover of the inversion graph
+// (Dilworth and König). The kept pages are then chained in their original order
+// and everything is sorted topologically around them.
+fn minimal_move_order(
+    update: &[u32],
+    ordering: &HashMap<u32, HashSet<u32>>,
+) -> Result<Correction, Cycle> {
+    let mut successors = restricted_successors(update, ordering);
+    topological_order(update, &successors)?;
+    let n = update.len();
+    let reach: Vec<Vec<bool>> = (0..n)
+        .map(|start| {
+            let mut reached = vec![false; n];
+            let mut stack = successors[start].clone();
+            while let Some(i) = stack.pop() {
+                if !reached[i] {
+                    reached[i] = true;
+                    stack.extend(&successors[i]);
+                }
+            }
+            reached
+        })
+        .collect();
+    let inversions: Vec<Vec<usize>> = (0..n)
+        .map(|i| (i + 1..n).filter(|j| reach[*j][i]).collect())
+        .collect();
+    let mut matched: Vec<Option<usize>> = vec![None; n];
+    for i in 0..n {
+        augment(i, &inversions, &mut matched, &mut vec![false; n]);
+    }
+    // Positions reachable by alternating paths from unmatched left vertices.
+    let mut left = vec![false; n];
+    let mut right = vec![false; n];
+    let mut stack: Vec<usize> = (0..n).filter(|i| !matched.contains(&Some(*i))).collect();
+    while let Some(i) = stack.pop() {
+        if left[i] {
+            continue;
+        }
+        left[i] = true;
+        for j in &inversions[i] {
+            if !right[*j] {
+                right[*j] = true;
+                stack.extend(matched[*j]);
+            }
+        }
+    }
+    let kept: Vec<usize> = (0..n).filter(|i| left[*i] && !right[*i]).collect();
+    for pair in kept.windows(2) {
+        successors[pair[0]].push(pair[1]);
+    }
+    let ordered = topological_order(update, &successors)?;
+    let moved = (0..n)
+        .filter(|i| !kept.contains(i))
+        .map(|i| update[i])
+        .collect();
+    Ok(Correction { ordered, moved })
+}
+
+fn minimal_moves(
+    ordering: &HashMap<u32, HashSet<u32>>,
+    updates: &[Vec<u32>],
+) -> Result<(String, u32), Cycle> {
+    let join = |pages: &[u32]| {
+        pages
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<String>>()
+            .join(",")
+    };
+    let mut lines = Vec::new();
+    let mut sum = 0;
+    for (idx, update) in updates.iter().enumerate() {
+        if is_ordered(update, ordering) {
+            continue;
+        }
+        let correction = minimal_move_order(update, ordering)?;
+        lines.push(format!(
+            "update {}: moved {} -> {}",
+            idx + 1,
+            join(&correction.moved),
+            join(&correction.ordered)
+        ));
+        sum += correction.ordered[correction.ordered.len() / 2];
+    }
+    Ok((lines.join("\n"), sum))
+}
+
+pub fn minimal_move_updates(path: Option<&str>) -> Result<(String, u32), String> {
+    let data = load_data(path)?;
+    minimal_moves(&data.ordering, &data.updates).map_err(|cycle| cycle.to_string())
+}
+
+// Findings about a rule file on its own, before any update is checked.
+#[derive(Debug, PartialEq, Eq)]
+struct RuleReport {
+    rules: usize,
+    // Lines of the file that could not be read as a rule or an update.
+    malformed: Vec<String>,
+    // Rules written more than once, with how often they appear.
+    duplicates: Vec<((u32, u32), usize)>,
+    // Pairs with both `a|b` and `b|a`, listed once with `a < b`.
+    contradictions: Vec<(u32, u32)>,
+    // One shortest cycle through each group of pages that all precede each other.
+    cycles: Vec<Cycle>,
+    // Rules implied by a chain of other rules. Only computed when there are no
+    // cycles, since a cyclic rule set has no unique transitive reduction.
+    redundant: Option<Vec<(u32, u32)>>,
+}
+
+// Pages reachable from each page through one or more rules.
+fn reachability(graph: &BTreeMap<u32, BTreeSet<u32>>) -> BTreeMap<u32, BTreeSet<u32>> {
+    graph
+        .keys()
+        .map(|start| {
+            let mut reached = BTreeSet::new();
+            let mut stack: Vec<u32> = graph[start].iter().copied().collect();
+            while let Some(page) = stack.pop() {
+                if reached.insert(page) {
+                    stack.extend(graph[&page].iter().copied());
+                }
+            }
+            (*start, reached)
+        })
+        .collect()
+}
+
+// Breadth-first search for the shortest way from `start` back to itself.
+fn shortest_cycle(graph: &BTreeMap<u32, BTreeSet<u32>>, start: u32) -> Cycle {
+    let mut previous: BTreeMap<u32, u32> = BTreeMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(page) = queue.pop_front() {
+        for next in &graph[&page] {
+            if *next == start {
+                let mut pages = vec![page];
+                while let Some(before) = previous.get(pages.last().unwrap()) {
+                    pages.push(*before);
+                }
+                pages.reverse();
+                return Cycle { pages };
+            }
+            if !previous.contains_key(next) {
+                previous.insert(*next, page);
+                queue.push_back(*next);
+            }
+        }
+    }
+    unreachable!("{} is not on a cycle", start)
+}
+
+fn validate(rules: &[(u32, u32)]) -> RuleReport {
+    let mut counts: BTreeMap<(u32, u32), usize> = BTreeMap::new();
+    let mut graph: BTreeMap<u32, BTreeSet<u32>> = BTreeMap::new();
+    for (before, after) in rules {
+        *counts.entry((*before, *after)).or_default() += 1;
+        graph.entry(*before).or_default().insert(*after);
+        graph.entry(*after).or_default();
+    }
+    let duplicates = counts
+        .iter()
+        .filter(|(_, count)| **count > 1)
+        .map(|(rule, count)| (*rule, *count))
+        .collect();
+    let contradictions = counts
+        .keys()
+        .filter(|(a, b)| a < b && counts.contains_key(&(*b, *a)))
+        .copied()
+        .collect();
+    let reach = reachability(&graph);
+    let mut cycles = Vec::new();
+    let mut grouped: BTreeSet<u32> = BTreeSet::new();
+    for page in graph.keys() {
+        if grouped.contains(page) || !reach[page].contains(page) {
+            continue;
+        }
+        grouped.extend(
+            reach[page]
+                .iter()
+                .filter(|other| reach[*other].contains(page)),
+        );
+        cycles.push(shortest_cycle(&graph, *page));
+    }
+    let redundant = cycles.is_empty().then(|| {
+        counts
+            .keys()
+            .filter(|(a, b)| {
+                graph[a]
+                    .iter()
+                    .any(|next| next != b && reach[next].contains(b))
+            })
+            .copied()
+            .collect()
+    });
+    RuleReport {
+        rules: rules.len(),
+        malformed: Vec::new(),
+        duplicates,
+        contradictions,
+        cycles,
+        redundant,
+    }
+}
+
+impl fmt::Display for RuleReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rule = |(a, b): &(u32, u32)| format!("{}|{}", a, b);
+        let list = |items: Vec<String>| {
+            if items.is_empty() {
+                "none".to_string()
+            } else {
+                items.join(", ")
+            }
+        };
+        writeln!(f, "rules: {}", self.rules)?;
+        writeln!(f, "malformed lines: {}", self.malformed.len())?;
+        for line in &self.malformed {
+            writeln!(f, "  {}", line)?;
+        }
+        let duplicates = self
+            .duplicates
+            .iter()
+            .map(|(r, count)| format!("{} (x{})", rule(r), count))
+            .collect();
+        writeln!(f, "duplicate rules: {}", list(duplicates))?;
+        let contradictions = self
+            .contradictions
+            .iter()
+            .map(|(a, b)| format!("{} and {}", rule(&(*a, *b)), rule(&(*b, *a))))
+            .collect();
+        writeln!(f, "contradictory pairs: {}", list(contradictions))?;
+        writeln!(f, "cycles: {}", self.cycles.len())?;
+        for cycle in &self.cycles {
+            writeln!(f, "  {}", cycle)?;
+        }
+        match &self.redundant {
+            Some(redundant) => write!(
+                f,
+                "redundant rules: {}",
+                list(redundant.iter().map(rule).collect())
+            ),
+            None => write!(f, "redundant rules: skipped, the rules are cyclic"),
+        }
+    }
+}
+
+pub fn validate_rules(path: Option<&str>) -> Result<String, String> {
+    let data = match path {
+        Some(path) => read_data(path)?,
+        None => get_data()?,
+    };
+    let report = RuleReport {
+        malformed: data.malformed,
+        ..validate(&data.rules)
+    };
+    Ok(report.to_string())
 }
 
-pub fn part_2() -> u32 {
-    let data = get_data();
-    challenge_02(&data.ordering, &data.updates)
+// Both answers for the data in `path`.
+pub fn input_parts(path: &str) -> Result<(u32, u32), String> {
+    let data = load_data(Some(path))?;
+    let sum = challenge_02(&data.ordering, &data.updates).map_err(|cycle| cycle.to_string())?;
+    Ok((challenge_01(&data.ordering, &data.updates), sum))
+}
+
+pub fn part_1() -> Result<u32, String> {
+    let data = load_data(None)?;
+    Ok(challenge_01(&data.ordering, &data.updates))
+}
+
+pub fn part_2() -> Result<u32, String> {
+    let data = load_data(None)?;
+    challenge_02(&data.ordering, &data.updates).map_err(|cycle| cycle.to_string())
 }
 
 #[cfg(test)]
@@ -148,7 +640,7 @@ mod test {
         let input = vec![75, 97, 47, 61, 53];
         let expected = vec![97, 75, 47, 61, 53];
         // Act
-        let actual = order(&input, &get_ordering());
+        let actual = order(&input, &get_ordering()).unwrap();
         // Assert
         assert_eq!(expected, actual);
     }
@@ -159,7 +651,7 @@ mod test {
         let input = vec![97, 13, 75, 29, 47];
         let expected = vec![97, 75, 47, 29, 13];
         // Act
-        let actual = order(&input, &get_ordering());
+        let actual = order(&input, &get_ordering()).unwrap();
         // Assert
         assert_eq!(expected, actual);
     }
@@ -170,7 +662,7 @@ mod test {
         let input = vec![61, 13, 29];
         let expected = vec![61, 29, 13];
         // Act
-        let actual = order(&input, &get_ordering());
+        let actual = order(&input, &get_ordering()).unwrap();
         // Assert
         assert_eq!(expected, actual);
     }
@@ -183,6 +675,261 @@ mod test {
         // Act
         let result = challenge_02(&ordering, &updates);
         // Assert
-        assert_eq!(result, 123);
+        assert_eq!(result, Ok(123));
+    }
+
+    #[test]
+    fn check_order_keeps_unrelated_pages() {
+        // Arrange
+        let ordering = HashMap::from([(5, HashSet::from([1]))]);
+        // Act
+        let actual = order(&[9, 1, 3, 5, 2], &ordering).unwrap();
+        // Assert
+        assert_eq!(actual, vec![9, 3, 5, 1, 2]);
+    }
+
+    #[test]
+    fn check_order_cycle() {
+        // Arrange
+        let mut ordering = get_ordering();
+        ordering.insert(13, HashSet::from([75]));
+        // Act
+        let actual = order(&[29, 75, 13, 97], &ordering);
+        // Assert
+        let cycle = actual.unwrap_err();
+        assert_eq!(cycle.pages, vec![29, 13, 75]);
+        assert_eq!(
+            cycle.to_string(),
+            "ordering rules form a cycle: 29 -> 13 -> 75 -> 29"
+        );
+        assert!(challenge_02(&ordering, &vec![vec![13, 75]]).is_err());
+    }
+
+    #[test]
+    fn check_validate_clean_rules() {
+        // Arrange
+        let rules = [(1, 2), (2, 3), (1, 3), (3, 4), (1, 4), (2, 5)];
+        // Act
+        let report = validate(&rules);
+        // Assert
+        assert!(report.duplicates.is_empty());
+        assert!(report.contradictions.is_empty());
+        assert!(report.cycles.is_empty());
+        assert_eq!(report.redundant, Some(vec![(1, 3), (1, 4)]));
+    }
+
+    #[test]
+    fn check_validate_faulty_rules() {
+        // Arrange
+        let rules = [
+            (1, 2),
+            (2, 1),
+            (1, 2),
+            (3, 4),
+            (4, 5),
+            (5, 3),
+            (6, 6),
+            (5, 7),
+        ];
+        // Act
+        let report = validate(&rules);
+        // Assert
+        assert_eq!(report.rules, 8);
+        assert_eq!(report.duplicates, vec![((1, 2), 2)]);
+        assert_eq!(report.contradictions, vec![(1, 2)]);
+        let cycles: Vec<Vec<u32>> = report.cycles.iter().map(|c| c.pages.clone()).collect();
+        assert_eq!(cycles, vec![vec![1, 2], vec![3, 4, 5], vec![6]]);
+        assert_eq!(report.redundant, None);
+        assert_eq!(
+            report.to_string(),
+            [
+                "rules: 8",
+                "malformed lines: 0",
+                "duplicate rules: 1|2 (x2)",
+                "contradictory pairs: 1|2 and 2|1",
+                "cycles: 3",
+                "  ordering rules form a cycle: 1 -> 2 -> 1",
+                "  ordering rules form a cycle: 3 -> 4 -> 5 -> 3",
+                "  ordering rules form a cycle: 6 -> 6",
+                "redundant rules: skipped, the rules are cyclic",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn check_malformed_lines() {
+        // Arrange
+        let input = "47|53\n47-53\n97|x\n\n75,47\n75,,47\n";
+        // Act
+        let data = parse_data(input);
+        let report = RuleReport {
+            malformed: data.malformed.clone(),
+            ..validate(&data.rules)
+        };
+        // Assert
+        assert_eq!(data.rules, vec![(47, 53)]);
+        assert_eq!(data.updates, vec![vec![75, 47]]);
+        assert_eq!(
+            data.malformed,
+            vec![
+                "line 2: expected a rule like 47|53, got \"47-53\"",
+                "line 3: expected a rule like 47|53, got \"97|x\"",
+                "line 6: expected comma separated pages, got \"75,,47\"",
+            ]
+        );
+        assert!(report
+            .to_string()
+            .contains("malformed lines: 3\n  line 2: expected a rule"));
+        assert!(read_data("data/missing_day05.txt").is_err());
+        assert!(load_data(Some("data/missing_day05.txt")).is_err());
+        assert!(load_data(None).is_ok_and(|data| data.malformed.is_empty()));
+    }
+
+    #[test]
+    fn check_violations() {
+        // Arrange
+        let ordering = get_ordering();
+        // Act
+        let actual = violations(&[97, 13, 75, 29, 47], &ordering);
+        // Assert
+        let rules: Vec<(u32, u32, usize, usize)> = actual
+            .iter()
+            .map(|v| (v.before, v.after, v.before_position, v.after_position))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                (75, 13, 2, 1),
+                (29, 13, 3, 1),
+                (47, 13, 4, 1),
+                (47, 29, 4, 3)
+            ]
+        );
+        for update in get_updates() {
+            assert_eq!(
+                violations(&update, &ordering).is_empty(),
+                is_ordered(&update, &ordering)
+            );
+        }
+    }
+
+    #[test]
+    fn check_explain() {
+        // Arrange
+        let ordering = get_ordering();
+        let updates = get_updates();
+        // Act
+        let actual = explain(&ordering, &updates);
+        // Assert
+        assert_eq!(
+            actual,
+            [
+                "update 4: 75,97,47,61,53",
+                "  97|75 broken: 97 at position 1, 75 at position 0",
+                "  corrected: 97,75,47,61,53",
+                "update 5: 61,13,29",
+                "  29|13 broken: 29 at position 2, 13 at position 1",
+                "  corrected: 61,29,13",
+                "update 6: 97,13,75,29,47",
+                "  75|13 broken: 75 at position 2, 13 at position 1",
+                "  29|13 broken: 29 at position 3, 13 at position 1",
+                "  47|13 broken: 47 at position 4, 13 at position 1",
+                "  47|29 broken: 47 at position 4, 29 at position 3",
+                "  corrected: 97,75,47,29,13",
+            ]
+            .join("\n")
+        );
+    }
+
+    // Fewest moves over every valid permutation: a page stays when it belongs to
+    // the longest common subsequence of the update and the permutation.
+    fn brute_force_moves(update: &[u32], ordering: &HashMap<u32, HashSet<u32>>) -> usize {
+        fn permutations(pages: &[u32]) -> Vec<Vec<u32>> {
+            if pages.is_empty() {
+                return vec![vec![]];
+            }
+            let mut all = Vec::new();
+            for i in 0..pages.len() {
+                let mut rest = pages.to_vec();
+                let page = rest.remove(i);
+                for mut tail in permutations(&rest) {
+                    tail.insert(0, page);
+                    all.push(tail);
+                }
+            }
+            all
+        }
+        let common = |a: &[u32], b: &[u32]| {
+            let mut table = vec![vec![0; b.len() + 1]; a.len() + 1];
+            for i in 0..a.len() {
+                for j in 0..b.len() {
+                    table[i + 1][j + 1] = if a[i] == b[j] {
+                        table[i][j] + 1
+                    } else {
+                        table[i][j + 1].max(table[i + 1][j])
+                    };
+                }
+            }
+            table[a.len()][b.len()]
+        };
+        permutations(update)
+            .iter()
+            .filter(|p| violations(p, ordering).is_empty())
+            .map(|p| update.len() - common(update, p))
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn check_minimal_move_order() {
+        // Arrange
+        let ordering = get_ordering();
+        let chain = HashMap::from([(1, HashSet::from([2])), (2, HashSet::from([3]))]);
+        let updates = vec![
+            (get_updates(), &ordering),
+            (vec![vec![3, 2, 1], vec![3, 1, 2], vec![2, 4, 3, 1]], &chain),
+        ];
+        for (updates, ordering) in updates {
+            for update in updates {
+                // Act
+                let correction = minimal_move_order(&update, ordering).unwrap();
+                // Assert
+                assert!(violations(&correction.ordered, ordering).is_empty());
+                let kept: Vec<u32> = update
+                    .iter()
+                    .filter(|p| !correction.moved.contains(p))
+                    .copied()
+                    .collect();
+                let placed: Vec<u32> = correction
+                    .ordered
+                    .iter()
+                    .filter(|p| kept.contains(p))
+                    .copied()
+                    .collect();
+                assert_eq!(kept, placed);
+                assert_eq!(correction.moved.len(), brute_force_moves(&update, ordering));
+            }
+        }
+    }
+
+    #[test]
+    fn check_minimal_moves() {
+        // Arrange
+        let ordering = HashMap::from([(1, HashSet::from([2])), (2, HashSet::from([3]))]);
+        // Act
+        let transitive = minimal_move_order(&[3, 2, 1], &ordering).unwrap();
+        let single = minimal_move_order(&[2, 3, 9, 1], &ordering).unwrap();
+        let (report, sum) = minimal_moves(&get_ordering(), &get_updates()).unwrap();
+        // Assert
+        assert_eq!(transitive.moved.len(), 2);
+        assert_eq!(transitive.ordered, vec![1, 2, 3]);
+        assert_eq!(single.moved, vec![1]);
+        assert_eq!(single.ordered, vec![1, 2, 3, 9]);
+        assert_eq!(sum, 123);
+        assert_eq!(report.lines().count(), 3);
+        let mut cyclic = get_ordering();
+        cyclic.insert(13, HashSet::from([75]));
+        assert!(minimal_move_order(&[29, 75, 13], &cyclic).is_err());
     }
 }
@@ -1,3 +1,5 @@
+use std::env;
+
 mod day_01;
 mod day_02;
 mod day_03;
@@ -10,6 +12,22 @@ mod day_09;
 
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => run_all(),
+        Some("day01") => run_day_01(&args[1..]),
+        Some("day02") => run_day_02(&args[1..]),
+        Some("day03") => run_day_03(&args[1..]),
+        Some("day04") => run_day_04(&args[1..]),
+        Some("day05") => run_day_05(&args[1..]),
+        Some(other) => {
+            eprintln!("Unknown command: {}", other);
+            std::process::exit(2);
+        }
+    }
+}
+
+fn run_all() {
     println!("Hello, world!");
     println!("Challenge 01 - part 1: {}", day_01::part_1());
     println!("Challenge 01 - part 2: {}", day_01::part_2());
@@ -19,8 +37,8 @@ fn main() {
     println!("Challenge 03 - part 2: {}", day_03::part_2());
     println!("Challenge 04 - part 1: {}", day_04::part_1());
     println!("Challenge 04 - part 2: {}", day_04::part_2());
-    println!("Challenge 05 - part 1: {}", day_05::part_1());
-    println!("Challenge 05 - part 2: {}", day_05::part_2());
+    println!("Challenge 05 - part 1: {}", day_05_answer(day_05::part_1()));
+    println!("Challenge 05 - part 2: {}", day_05_answer(day_05::part_2()));
     println!("Challenge 06 - part 1: {}", day_06::part_1());
     println!("Challenge 06 - part 2: {}", day_06::part_2());
     println!("Challenge 07 - part 1: {}", day_07::part_1());
@@ -29,3 +47,304 @@ fn main() {
     println!("Challenge 08 - part 2: {}", day_08::part_2());
     println!("Challenge 09 - part 1: {}", day_09::part_1());
 }
+
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
+
+fn parse_flag<T: std::str::FromStr>(args: &[String], flag: &str, default: T) -> T {
+    match flag_value(args, flag) {
+        Some(value) => value.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid value for {}: {}", flag, value);
+            std::process::exit(2);
+        }),
+        None => default,
+    }
+}
+
+// day01 --external <path> [--run-size <n>]
+fn run_day_01(args: &[String]) {
+    match flag_value(args, "--external") {
+        Some(path) => {
+            let run_size = parse_flag(args, "--run-size", 1_000_000usize);
+            match day_01::streaming_parts(path, run_size) {
+                Ok((part_1, part_2)) => {
+                    println!("Challenge 01 - part 1: {}", part_1);
+                    println!("Challenge 01 - part 2: {}", part_2);
+                }
+                Err(e) => {
+                    eprintln!("Failed to process {}: {}", path, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => {
+            println!("Challenge 01 - part 1: {}", day_01::part_1());
+            println!("Challenge 01 - part 2: {}", day_01::part_2());
+        }
+    }
+}
+
+// day02 [--min-step <n>] [--max-step <n>] [--direction increasing|decreasing|either] [--allow-equal]
+//       [--max-removals <k>] [--explain] [--stats table|json]
+fn day_02_policy(args: &[String]) -> day_02::SafetyPolicy {
+    let default = day_02::SafetyPolicy::default();
+    let policy = day_02::SafetyPolicy {
+        min_step: parse_flag(args, "--min-step", default.min_step),
+        max_step: parse_flag(args, "--max-step", default.max_step),
+        direction: parse_flag(args, "--direction", default.direction),
+        allow_equal: default.allow_equal || args.iter().any(|a| a == "--allow-equal"),
+    };
+    if let Err(e) = policy.validate() {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    }
+    policy
+}
+
+fn run_day_02(args: &[String]) {
+    let policy = day_02_policy(args);
+    if args.iter().any(|a| a == "--explain") {
+        println!("{}", day_02::explain_with_policy(&policy));
+    }
+    if let Some(format) = flag_value(args, "--stats") {
+        let json = match format {
+            "table" => false,
+            "json" => true,
+            _ => {
+                eprintln!("Invalid value for --stats: {}", format);
+                std::process::exit(2);
+            }
+        };
+        println!("{}", day_02::statistics_with_policy(&policy, json));
+    }
+    println!("Challenge 02 - part 1: {}", day_02::part_1_with_policy(&policy));
+    println!("Challenge 02 - part 2: {}", day_02::part_2_with_policy(&policy));
+    if flag_value(args, "--max-removals").is_some() {
+        let max_removals = parse_flag(args, "--max-removals", 1usize);
+        println!(
+            "Challenge 02 - safe within {} removals: {}",
+            max_removals,
+            day_02::safe_within_removals(&policy, max_removals)
+        );
+    }
+}
+
+// day03 [--ops <name,name,...>] [--grammar <path>] [--stream [--chunk-size <n>]]
+//       [--trace text|jsonl] [--diagnose] [--nested <depth>] [--bench [--iterations <n>]]
+fn run_day_03(args: &[String]) {
+    if args.iter().any(|a| a == "--bench") {
+        let iterations = parse_flag(args, "--iterations", 100usize);
+        let (regex, bytes) = day_03::benchmark_scanners(iterations);
+        println!("Challenge 03 - regex scanner: {:?} for {} runs", regex, iterations);
+        println!("Challenge 03 - byte scanner: {:?} for {} runs", bytes, iterations);
+        return;
+    }
+    if args.iter().any(|a| a == "--stream") {
+        let chunk_size = parse_flag(args, "--chunk-size", 4096usize);
+        match day_03::stream_parts(chunk_size) {
+            Ok((part_1, part_2)) => {
+                println!("Challenge 03 - part 1: {}", part_1);
+                println!("Challenge 03 - part 2: {}", part_2);
+            }
+            Err(e) => {
+                eprintln!("Failed to stream day 3 input: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    let grammar = match flag_value(args, "--grammar") {
+        Some(path) => day_03::Grammar::from_file(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }),
+        None => day_03::Grammar::default(),
+    };
+    let custom = flag_value(args, "--ops").is_some() || flag_value(args, "--grammar").is_some();
+    let ops = flag_value(args, "--ops").unwrap_or("mul,do,don't");
+    let names: Vec<&str> = ops.split(',').collect();
+    let report = if let Some(format) = flag_value(args, "--trace") {
+        day_03::trace_instruction_set(&names, &grammar, format)
+    } else if args.iter().any(|a| a == "--diagnose") {
+        day_03::diagnose_instruction_set(&names, &grammar)
+    } else if flag_value(args, "--nested").is_some() {
+        let depth = parse_flag(args, "--nested", 1usize);
+        day_03::nested_instruction_set(&names, &grammar, depth)
+            .map(|sum| format!("Challenge 03 - nested (depth {}): {}", depth, sum))
+    } else if custom {
+        day_03::run_instruction_set(&names, &grammar)
+            .map(|sum| format!("Challenge 03 - {}: {}", ops, sum))
+    } else {
+        Ok(format!(
+            "Challenge 03 - part 1: {}\nChallenge 03 - part 2: {}",
+            day_03::part_1(),
+            day_03::part_2()
+        ))
+    };
+    match report {
+        Ok(report) => println!("{}", report),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+// day04 [--word <word> [--directions eight|four|forward] [--wrap]]
+//       [--pattern <row/row/...> [--rotations] [--reflections]]
+//       [--words <word,word,...>] [--word-list <path>] [--render 1|2]
+//       [--generate <width>x<height> [--plant <word:copies,...>] [--x-mas <n>] [--overlap]
+//        [--seed <n>] [--directions eight|four|forward] [--alphabet <letters>]]
+//       [--large <path>]
+fn day_04_puzzle_spec(size: &str, args: &[String]) -> Option<day_04::PuzzleSpec> {
+    let (width, height) = size.split_once('x')?;
+    let mut words = Vec::new();
+    if let Some(plant) = flag_value(args, "--plant") {
+        for entry in plant.split(',') {
+            let (word, copies) = entry.split_once(':').unwrap_or((entry, "1"));
+            words.push((word.to_string(), copies.parse().ok()?));
+        }
+    }
+    Some(day_04::PuzzleSpec {
+        width: width.parse().ok()?,
+        height: height.parse().ok()?,
+        words,
+        directions: flag_value(args, "--directions").unwrap_or("eight").to_string(),
+        x_mas: parse_flag(args, "--x-mas", 0usize),
+        overlap: args.iter().any(|a| a == "--overlap"),
+        alphabet: flag_value(args, "--alphabet").unwrap_or("XMAS").to_string(),
+        seed: parse_flag(args, "--seed", 0u64),
+    })
+}
+
+fn run_day_04(args: &[String]) {
+    if let Some(path) = flag_value(args, "--large") {
+        match day_04::large_parts(path) {
+            Ok((part_1, part_2)) => {
+                println!("Challenge 04 - part 1: {}", part_1);
+                println!("Challenge 04 - part 2: {}", part_2);
+            }
+            Err(e) => {
+                eprintln!("Failed to process {}: {}", path, e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(size) = flag_value(args, "--generate") {
+        let spec = day_04_puzzle_spec(size, args).unwrap_or_else(|| {
+            eprintln!("Invalid puzzle size or plant list: {}", size);
+            std::process::exit(2);
+        });
+        match day_04::generate_puzzle(&spec) {
+            Ok(puzzle) => println!("{}", puzzle),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+        }
+        return;
+    }
+    if flag_value(args, "--render").is_some() {
+        let part = parse_flag(args, "--render", 1usize);
+        match day_04::render_part(part) {
+            Some(rendered) => println!("{}", rendered),
+            None => {
+                eprintln!("Invalid value for --render: {}", part);
+                std::process::exit(2);
+            }
+        }
+        return;
+    }
+    let mut words: Vec<String> = flag_value(args, "--words")
+        .map(|list| list.split(',').map(String::from).collect())
+        .unwrap_or_default();
+    if let Some(path) = flag_value(args, "--word-list") {
+        match day_04::read_word_list(path) {
+            Ok(list) => words.extend(list),
+            Err(e) => {
+                eprintln!("Failed to read {}: {}", path, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if !words.is_empty() {
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        for (word, count) in words.iter().zip(day_04::count_words(&words)) {
+            println!("Challenge 04 - {}: {}", word, count);
+        }
+        return;
+    }
+    if let Some(pattern) = flag_value(args, "--pattern") {
+        let rotations = args.iter().any(|a| a == "--rotations");
+        let reflections = args.iter().any(|a| a == "--reflections");
+        match day_04::count_pattern(pattern, rotations, reflections) {
+            Ok(count) => println!("Challenge 04 - {}: {}", pattern, count),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+        }
+        return;
+    }
+    match flag_value(args, "--word") {
+        Some(word) => {
+            let directions = flag_value(args, "--directions").unwrap_or("eight");
+            let wrapping = args.iter().any(|a| a == "--wrap");
+            match day_04::count_word(word, directions, wrapping) {
+                Ok(count) => println!("Challenge 04 - {} ({}): {}", word, directions, count),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(2);
+                }
+            }
+        }
+        None => {
+            println!("Challenge 04 - part 1: {}", day_04::part_1());
+            println!("Challenge 04 - part 2: {}", day_04::part_2());
+        }
+    }
+}
+
+// day05 [--validate] [--explain] [--minimal] [--input <path>]
+fn run_day_05(args: &[String]) {
+    let input = flag_value(args, "--input");
+    let or_exit = |e: String| -> ! {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    };
+    if args.iter().any(|a| a == "--validate") {
+        println!("{}", day_05::validate_rules(input).unwrap_or_else(|e| or_exit(e)));
+        return;
+    }
+    if args.iter().any(|a| a == "--explain") {
+        println!("{}", day_05::explain_updates(input).unwrap_or_else(|e| or_exit(e)));
+        return;
+    }
+    if args.iter().any(|a| a == "--minimal") {
+        let (report, sum) = day_05::minimal_move_updates(input).unwrap_or_else(|e| or_exit(e));
+        println!("{}", report);
+        println!("Challenge 05 - part 2 (minimal moves): {}", sum);
+        return;
+    }
+    if let Some(path) = input {
+        let (part_1, part_2) = day_05::input_parts(path).unwrap_or_else(|e| or_exit(e));
+        println!("Challenge 05 - part 1: {}", part_1);
+        println!("Challenge 05 - part 2: {}", part_2);
+        return;
+    }
+    println!("Challenge 05 - part 1: {}", day_05_answer(day_05::part_1()));
+    println!("Challenge 05 - part 2: {}", day_05_answer(day_05::part_2()));
+}
+
+// The bundled input may be unreadable, malformed, or have cyclic rules.
+fn day_05_answer(answer: Result<u32, String>) -> u32 {
+    answer.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    })
+}